@@ -0,0 +1,36 @@
+//! Compare the metadata queries against the running players:
+//! `cargo run --release --example metadata_bench [ITERATIONS]`.
+
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use playerctl_rust_wrapper::{PlayerMetadata, Playerctl, Result};
+
+fn bench<T>(name: &str, iterations: u32, query: impl Fn() -> Result<T>) {
+    let start = Instant::now();
+    for _ in 0..iterations {
+        query().expect("metadata query failed");
+    }
+    let per_call: Duration = start.elapsed() / iterations;
+    println!("{name}: {per_call:?} per call");
+}
+
+fn main() {
+    let iterations = std::env::args()
+        .nth(1)
+        .map(|arg| arg.parse().expect("ITERATIONS must be a number"))
+        .unwrap_or(50);
+    bench("metadata", iterations, Playerctl::metadata);
+    bench("metadata_fast", iterations, Playerctl::metadata_fast);
+
+    let players = Playerctl::list_players().expect("listing players failed");
+    let names: Vec<_> = players.iter().map(String::as_str).collect();
+    let names = &names;
+    let for_many = |threads| move || -> Result<HashMap<String, Result<PlayerMetadata>>> {
+        Ok(Playerctl::metadata_for_many(names, threads))
+    };
+    bench("metadata_for_many, sequential", iterations, for_many(1));
+    bench("metadata_for_many, 4 threads", iterations, for_many(4));
+}
@@ -0,0 +1,117 @@
+//! Memoizing art lookups across polls.
+
+use std::{fs, io, path::PathBuf};
+
+use crate::{PlayerMetadata, Playerctl};
+
+/// Cache of the current track's art, for callers polling metadata.
+///
+/// The art path and bytes are reused while the track id (`mpris:trackid`)
+/// and art URL stay the same, and dropped when either changes. Only one
+/// track's art is kept, so with [`art_bytes`](Self::art_bytes) the cache
+/// holds at most one art file in memory.
+///
+/// ```no_run
+/// # use playerctl_rust_wrapper::Playerctl;
+/// let mut cache = Playerctl::cached();
+/// loop {
+///     let metadata = Playerctl::metadata().unwrap();
+///     if let Some(art) = metadata.get("mpv").and_then(|m| cache.art_bytes(m)) {
+///         println!("{} bytes of art", art.unwrap().len());
+///     }
+///     std::thread::sleep(std::time::Duration::from_secs(1));
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct CachedPlayerctl {
+    art: Option<CachedArt>,
+}
+
+/// Art of the track with the given id and art URL.
+#[derive(Debug)]
+struct CachedArt {
+    trackid: Option<String>,
+    art_url: Option<String>,
+    path: Option<PathBuf>,
+    bytes: Option<Vec<u8>>,
+}
+
+impl CachedPlayerctl {
+    /// Local path of the track's art, see [`PlayerMetadata::art_path`].
+    pub fn art_path(&mut self, metadata: &PlayerMetadata) -> Option<PathBuf> {
+        self.entry(metadata).path.clone()
+    }
+
+    /// The track's art read into memory, see [`PlayerMetadata::art_bytes`].
+    /// Read errors are not cached, so the next call tries again.
+    pub fn art_bytes(&mut self, metadata: &PlayerMetadata) -> Option<io::Result<&[u8]>> {
+        let art = self.entry(metadata);
+        if art.bytes.is_none() {
+            match fs::read(art.path.as_ref()?) {
+                Ok(bytes) => art.bytes = Some(bytes),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        art.bytes.as_deref().map(Ok)
+    }
+
+    /// Drop the cached art.
+    pub fn invalidate(&mut self) {
+        self.art = None;
+    }
+
+    /// The cache entry for `metadata`'s track, replacing a stale one.
+    fn entry(&mut self, metadata: &PlayerMetadata) -> &mut CachedArt {
+        let stale = self.art.as_ref().is_none_or(|art| {
+            art.trackid != metadata.mpris_trackid || art.art_url != metadata.mpris_art_url
+        });
+        if stale {
+            self.art = None;
+        }
+        self.art.get_or_insert_with(|| CachedArt {
+            trackid: metadata.mpris_trackid.clone(),
+            art_url: metadata.mpris_art_url.clone(),
+            path: metadata.art_path(),
+            bytes: None,
+        })
+    }
+}
+
+impl Playerctl {
+    /// Get an empty [`CachedPlayerctl`].
+    pub fn cached() -> CachedPlayerctl {
+        CachedPlayerctl::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(trackid: &str, path: &std::path::Path) -> PlayerMetadata {
+        PlayerMetadata {
+            mpris_trackid: Some(trackid.to_owned()),
+            mpris_art_url: Some(format!("file://{}", path.display())),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn reuses_art_while_track_is_unchanged() {
+        let path = std::env::temp_dir().join(format!("playerctl-cached-art-{}.png", std::process::id()));
+        fs::write(&path, b"art").unwrap();
+        let mut cache = CachedPlayerctl::default();
+        assert_eq!(cache.art_bytes(&track("/1", &path)).unwrap().unwrap(), b"art");
+
+        // The file isn't read again for the same track.
+        fs::write(&path, b"new art").unwrap();
+        assert_eq!(cache.art_bytes(&track("/1", &path)).unwrap().unwrap(), b"art");
+        assert_eq!(cache.art_path(&track("/1", &path)), Some(path.clone()));
+
+        // A new track id invalidates the cache.
+        assert_eq!(cache.art_bytes(&track("/2", &path)).unwrap().unwrap(), b"new art");
+        fs::remove_file(&path).unwrap();
+        assert!(cache.art_bytes(&track("/3", &path)).unwrap().is_err());
+        assert!(cache.art_bytes(&PlayerMetadata::default()).is_none());
+    }
+}
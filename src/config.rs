@@ -0,0 +1,230 @@
+//! Options shared by all commands of a [`PlayerctlHandle`](crate::PlayerctlHandle).
+
+use std::{fmt, path::PathBuf, process::Command, sync::Arc, time::Duration};
+
+use crate::{PlayerMetadata, Result};
+
+/// Template used by the display helpers unless another default is set.
+pub const DEFAULT_FORMAT: &str = "{{artist}} - {{title}}";
+
+/// Callback receiving the stderr output of playerctl commands, see
+/// [`PlayerctlConfig::stderr_callback`].
+pub type StderrCallback = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// Hook called with the arguments of every command just before it runs, see
+/// [`PlayerctlConfig::before_command`].
+pub type BeforeCommandHook = Arc<dyn Fn(&[&str]) + Send + Sync>;
+
+/// Hook called with the arguments, result and duration of every command
+/// after it ran, see [`PlayerctlConfig::after_command`].
+pub type AfterCommandHook = Arc<dyn Fn(&[&str], &Result<String>, Duration) + Send + Sync>;
+
+/// Configuration of a [`PlayerctlHandle`](crate::PlayerctlHandle), applied
+/// to every playerctl command it runs.
+///
+/// The static [`Playerctl`](crate::Playerctl) functions run through a
+/// handle with [`PlayerctlConfig::default`]. Each of them that talks to
+/// playerctl has a handle method of the same name honouring these options.
+///
+/// The metadata options ([`decode_raw_urls`](Self::decode_raw_urls),
+/// [`decode_html_entities`](Self::decode_html_entities),
+/// [`normalize_whitespace`](Self::normalize_whitespace) and
+/// [`fix_nanosecond_lengths`](Self::fix_nanosecond_lengths)) apply to every
+/// [`PlayerMetadata`] returned by the handle, including followed events.
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// # use playerctl_rust_wrapper::{Playerctl, PlayerctlConfig};
+/// let playerctl = Playerctl::with_config(PlayerctlConfig {
+///     timeout: Some(Duration::from_secs(2)),
+///     retries: 1,
+///     ..Default::default()
+/// });
+/// playerctl.play().unwrap();
+/// ```
+#[derive(Clone)]
+pub struct PlayerctlConfig {
+    /// Name or path of the playerctl binary. Defaults to `playerctl`.
+    pub binary: PathBuf,
+    /// Kill commands running longer than this, failing with
+    /// [`PlayerctlError::Timeout`](crate::PlayerctlError::Timeout). Defaults
+    /// to no timeout. Single calls can override it with
+    /// [`PlayerctlHandle::call_timeout`](crate::PlayerctlHandle::call_timeout).
+    pub timeout: Option<Duration>,
+    /// Additional environment variables for playerctl.
+    pub env: Vec<(String, String)>,
+    /// How often a failed command is retried. Only generic command failures
+    /// and I/O errors are retried, not e.g. a missing player, an unsupported
+    /// command, a timeout or a binary that can't be found.
+    /// Defaults to 0.
+    pub retries: usize,
+    /// Run playerctl with `LC_ALL=C`, so its output doesn't depend on the
+    /// user's locale. Defaults to `false`.
+    pub lc_all_c: bool,
+    /// The `--format` template used by the display helpers when no template
+    /// is passed to them. Defaults to [`DEFAULT_FORMAT`].
+    pub format_default: String,
+    /// Percent-decode URL values (`mpris:artUrl`, `xesam:url`) in
+    /// [`PlayerMetadata::raw`](crate::PlayerMetadata::raw) as well. The typed
+    /// fields are always decoded. Defaults to `false`, so `raw` holds the
+    /// values exactly as playerctl emitted them.
+    pub decode_raw_urls: bool,
+    /// Called with the stderr output of every command that prints to
+    /// stderr, even if it succeeds, as some players warn on stderr while
+    /// succeeding. The callback runs synchronously on the thread running the
+    /// command, before its result is returned. Defaults to `None`.
+    pub stderr_callback: Option<StderrCallback>,
+    /// Working directory of playerctl, against which relative paths passed
+    /// to `open` resolve. Defaults to `None`, inheriting the current
+    /// directory of this process.
+    pub working_directory: Option<PathBuf>,
+    /// Decode HTML entities in the title and artist, as emitted by some
+    /// browser-based players (`&amp;`, `&lt;`, `&gt;`, `&quot;`, `&apos;`
+    /// and numeric entities like `&#39;` or `&#x27;`). Only the typed
+    /// fields are decoded, `raw` keeps the values as emitted. Defaults to
+    /// `false`.
+    pub decode_html_entities: bool,
+    /// Clean up the whitespace of the title and artist, as emitted by some
+    /// web players: non-breaking spaces (U+00A0) become regular spaces, runs
+    /// of whitespace collapse into a single space, and leading and trailing
+    /// whitespace is removed. Other characters, including smart quotes, are
+    /// kept. Applied after
+    /// [`decode_html_entities`](Self::decode_html_entities); `raw` keeps the
+    /// values as emitted. Defaults to `false`.
+    pub normalize_whitespace: bool,
+    /// Take lengths above
+    /// [`NANOSECOND_LENGTH_THRESHOLD`](crate::NANOSECOND_LENGTH_THRESHOLD)
+    /// as nanoseconds, as reported by a few broken players, and convert them
+    /// to microseconds. With the `log` feature, every conversion is logged
+    /// as a warning. Only the typed `mpris_length` is converted, `raw` keeps
+    /// the value as emitted. Defaults to `false`, as audiobooks and streams
+    /// can legitimately run longer.
+    pub fix_nanosecond_lengths: bool,
+    /// Run playerctl with this niceness, e.g. `10` for daemons polling often
+    /// that shouldn't contend with foreground apps. Like `nice -n`, this is
+    /// added to the niceness of this process in the child, with `nice(2)`,
+    /// so this is only available on Unix. Negative values need privileges
+    /// and are ignored without them. Defaults to `None`, inheriting the
+    /// niceness of this process.
+    #[cfg(unix)]
+    pub niceness: Option<i32>,
+    /// Called with the arguments of every command just before it runs, e.g.
+    /// to count spawns. Retries run the hooks again. Like the
+    /// [`stderr_callback`](Self::stderr_callback), hooks run synchronously
+    /// on the thread running the command, so they should return quickly.
+    /// Defaults to `None`.
+    pub before_command: Option<BeforeCommandHook>,
+    /// Called with the arguments, result and duration of every command after
+    /// it ran, e.g. to measure latency. See
+    /// [`before_command`](Self::before_command). Defaults to `None`.
+    pub after_command: Option<AfterCommandHook>,
+}
+
+impl fmt::Debug for PlayerctlConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("PlayerctlConfig");
+        debug
+            .field("binary", &self.binary)
+            .field("timeout", &self.timeout)
+            .field("env", &self.env)
+            .field("retries", &self.retries)
+            .field("lc_all_c", &self.lc_all_c)
+            .field("format_default", &self.format_default)
+            .field("decode_raw_urls", &self.decode_raw_urls)
+            .field("stderr_callback", &self.stderr_callback.as_ref().map(|_| ".."))
+            .field("working_directory", &self.working_directory)
+            .field("decode_html_entities", &self.decode_html_entities)
+            .field("normalize_whitespace", &self.normalize_whitespace)
+            .field("fix_nanosecond_lengths", &self.fix_nanosecond_lengths);
+        #[cfg(unix)]
+        debug.field("niceness", &self.niceness);
+        debug
+            .field("before_command", &self.before_command.as_ref().map(|_| ".."))
+            .field("after_command", &self.after_command.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
+
+impl Default for PlayerctlConfig {
+    fn default() -> Self {
+        Self {
+            binary: PathBuf::from("playerctl"),
+            timeout: None,
+            env: Vec::new(),
+            retries: 0,
+            lc_all_c: false,
+            format_default: DEFAULT_FORMAT.to_owned(),
+            decode_raw_urls: false,
+            stderr_callback: None,
+            working_directory: None,
+            decode_html_entities: false,
+            normalize_whitespace: false,
+            fix_nanosecond_lengths: false,
+            #[cfg(unix)]
+            niceness: None,
+            before_command: None,
+            after_command: None,
+        }
+    }
+}
+
+#[cfg(unix)]
+extern "C" {
+    /// Add `inc` to the niceness of the calling process, see `nice(2)`.
+    fn nice(inc: std::ffi::c_int) -> std::ffi::c_int;
+}
+
+impl PlayerctlConfig {
+    /// Build a playerctl command with the given arguments and this
+    /// configuration applied.
+    pub(crate) fn command(&self, args: &[&str]) -> Command {
+        let mut command = Command::new(&self.binary);
+        #[cfg(unix)]
+        if let Some(niceness) = self.niceness {
+            use std::os::unix::process::CommandExt;
+            // SAFETY: nice(2) is async-signal-safe and doesn't allocate. As
+            // with `nice -n`, failing to change the niceness isn't fatal.
+            unsafe {
+                command.pre_exec(move || {
+                    nice(niceness);
+                    Ok(())
+                });
+            }
+        }
+        command.args(args);
+        command.envs(self.env.iter().map(|(key, val)| (key, val)));
+        if self.lc_all_c {
+            command.env("LC_ALL", "C");
+        }
+        if let Some(dir) = &self.working_directory {
+            command.current_dir(dir);
+        }
+        command
+    }
+
+    /// Apply the metadata options of this configuration to `metadata`.
+    pub(crate) fn postprocess(&self, metadata: &mut PlayerMetadata) -> Result<()> {
+        if self.decode_raw_urls {
+            metadata.decode_raw_urls()?;
+        }
+        if self.decode_html_entities {
+            metadata.decode_html_entities();
+        }
+        if self.normalize_whitespace {
+            metadata.normalize_whitespace();
+        }
+        if self.fix_nanosecond_lengths {
+            metadata.fix_nanosecond_length();
+        }
+        Ok(())
+    }
+
+    /// Pass non-empty `stderr` to the [`stderr_callback`](Self::stderr_callback).
+    pub(crate) fn report_stderr(&self, stderr: &[u8]) {
+        if let Some(callback) = &self.stderr_callback {
+            if !stderr.is_empty() {
+                callback(&String::from_utf8_lossy(stderr));
+            }
+        }
+    }
+}
@@ -0,0 +1,148 @@
+//! Player-agnostic playback control.
+
+use crate::{PlayerInstance, Playerctl, PlayerctlHandle, Result};
+
+/// Playback commands shared by the default player ([`Playerctl`]), a named
+/// player ([`PlayerInstance`]) and a configured handle ([`PlayerctlHandle`]),
+/// so code can control either without knowing which it got.
+///
+/// ```no_run
+/// # use playerctl_rust_wrapper::{PlayerControls, Playerctl};
+/// fn on_next_key(target: &dyn PlayerControls) {
+///     target.next().unwrap();
+/// }
+///
+/// on_next_key(&*Playerctl::controls(None));
+/// on_next_key(&*Playerctl::controls(Some("mpv")));
+/// ```
+pub trait PlayerControls: Send + Sync {
+    /// Command the player to play.
+    fn play(&self) -> Result<()>;
+    /// Command the player to pause.
+    fn pause(&self) -> Result<()>;
+    /// Command the player to toggle between play/pause.
+    fn play_pause(&self) -> Result<()>;
+    /// Command the player to stop.
+    fn stop(&self) -> Result<()>;
+    /// Command the player to skip to the next track.
+    fn next(&self) -> Result<()>;
+    /// Command the player to skip to the previous track.
+    fn previous(&self) -> Result<()>;
+}
+
+impl PlayerControls for Playerctl {
+    fn play(&self) -> Result<()> {
+        Playerctl::play()
+    }
+
+    fn pause(&self) -> Result<()> {
+        Playerctl::pause()
+    }
+
+    fn play_pause(&self) -> Result<()> {
+        Playerctl::play_pause()
+    }
+
+    fn stop(&self) -> Result<()> {
+        Playerctl::stop()
+    }
+
+    fn next(&self) -> Result<()> {
+        Playerctl::next()
+    }
+
+    fn previous(&self) -> Result<()> {
+        Playerctl::previous()
+    }
+}
+
+impl PlayerControls for PlayerInstance {
+    fn play(&self) -> Result<()> {
+        PlayerInstance::play(self)
+    }
+
+    fn pause(&self) -> Result<()> {
+        PlayerInstance::pause(self)
+    }
+
+    fn play_pause(&self) -> Result<()> {
+        PlayerInstance::play_pause(self)
+    }
+
+    fn stop(&self) -> Result<()> {
+        PlayerInstance::stop(self)
+    }
+
+    fn next(&self) -> Result<()> {
+        PlayerInstance::next(self)
+    }
+
+    fn previous(&self) -> Result<()> {
+        PlayerInstance::previous(self)
+    }
+}
+
+impl PlayerControls for PlayerctlHandle {
+    fn play(&self) -> Result<()> {
+        PlayerctlHandle::play(self)
+    }
+
+    fn pause(&self) -> Result<()> {
+        PlayerctlHandle::pause(self)
+    }
+
+    fn play_pause(&self) -> Result<()> {
+        PlayerctlHandle::play_pause(self)
+    }
+
+    fn stop(&self) -> Result<()> {
+        PlayerctlHandle::stop(self)
+    }
+
+    fn next(&self) -> Result<()> {
+        PlayerctlHandle::next(self)
+    }
+
+    fn previous(&self) -> Result<()> {
+        PlayerctlHandle::previous(self)
+    }
+}
+
+impl Playerctl {
+    /// Get a control target for the player `name`, or for the default
+    /// player if `name` is `None`.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// let target = Playerctl::controls(std::env::args().nth(1).as_deref());
+    /// target.play_pause().unwrap();
+    /// ```
+    pub fn controls(name: Option<&str>) -> Box<dyn PlayerControls> {
+        match name {
+            Some(name) => Box::new(Playerctl::player(name)),
+            None => Box::new(Playerctl),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{calls, mock, ok};
+
+    fn skip_twice(target: &dyn PlayerControls) {
+        target.next().unwrap();
+        target.previous().unwrap();
+    }
+
+    #[test]
+    fn controls_default_and_named_players() {
+        mock([ok(""), ok(""), ok(""), ok("")]);
+        skip_twice(&*Playerctl::controls(None));
+        skip_twice(&*Playerctl::controls(Some("mpv")));
+        assert_eq!(
+            calls(),
+            [vec!["next"], vec!["previous"], vec!["-p", "mpv", "next"], vec!["-p", "mpv", "previous"]]
+        );
+    }
+}
@@ -0,0 +1,721 @@
+//! Following metadata changes with `playerctl --follow`.
+
+use std::{
+    collections::{HashSet, VecDeque},
+    io::{BufRead, BufReader, Lines},
+    process::{Child, Command, Stdio},
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use crate::{list_players_with, PlayerMetadata, Playerctl, PlayerctlConfig, PlayerctlError, PlayerctlHandle, Result};
+
+/// Metadata keys requested in follow mode, in template order.
+const FOLLOW_KEYS: [&str; 9] = [
+    "mpris:trackid",
+    "mpris:artUrl",
+    "mpris:length",
+    "xesam:title",
+    "xesam:album",
+    "xesam:artist",
+    "xesam:albumArtist",
+    "xesam:url",
+    "xesam:contentCreated",
+];
+
+/// Separator between the fields of a followed line (ASCII unit separator).
+pub(crate) const SEPARATOR: char = '\u{1f}';
+
+/// An event reported while following players.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+// Most events carry metadata, so boxing it would just add an allocation.
+#[allow(clippy::large_enum_variant)]
+pub enum PlayerEvent {
+    /// The metadata of a player changed.
+    Metadata {
+        player: String,
+        metadata: PlayerMetadata,
+    },
+    /// A player started, reported just before its first
+    /// [`Metadata`](Self::Metadata) event.
+    PlayerAppeared(String),
+    /// A player exited.
+    PlayerDisappeared(String),
+}
+
+/// A running `playerctl --follow` process, read line by line.
+struct FollowProcess {
+    child: Option<Child>,
+    lines: Lines<Box<dyn BufRead + Send>>,
+}
+
+impl FollowProcess {
+    fn spawn_command(mut command: Command) -> Result<Self> {
+        let mut child = command
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| PlayerctlError::Other("playerctl stdout is not captured".to_owned()))?;
+        let mut process = Self::from_reader(BufReader::new(stdout));
+        process.child = Some(child);
+        Ok(process)
+    }
+
+    /// Follow the output of an already running playerctl process.
+    fn from_reader(reader: impl BufRead + Send + 'static) -> Self {
+        let reader: Box<dyn BufRead + Send> = Box::new(reader);
+        Self { child: None, lines: reader.lines() }
+    }
+
+    fn next_line(&mut self) -> Option<Result<String>> {
+        Some(self.lines.next()?.map_err(PlayerctlError::from))
+    }
+
+    fn stop(&mut self) -> Result<()> {
+        if let Some(mut child) = self.child.take() {
+            child.kill()?;
+            child.wait()?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for FollowProcess {
+    /// Kill and reap the child, so dropped followers don't leave `playerctl`
+    /// processes behind.
+    fn drop(&mut self) {
+        let _ = self.stop();
+    }
+}
+
+/// Iterator over [`PlayerEvent`]s of all players, backed by a running
+/// `playerctl --follow` process.
+///
+/// The process is terminated when the iterator is dropped.
+///
+/// Only the typed metadata fields are followed, so `raw` contains just those
+/// keys.
+///
+/// Players already running when following starts are reported as
+/// [appeared](PlayerEvent::PlayerAppeared) too. playerctl doesn't say which
+/// player exited, so on every exit the player list is fetched to find out.
+pub struct Follow {
+    process: FollowProcess,
+    /// Options applied to the metadata and to fetching the player list.
+    config: PlayerctlConfig,
+    /// Players reported as appeared and not yet as disappeared.
+    known: HashSet<String>,
+    /// Events to yield before reading the next line.
+    queued: VecDeque<PlayerEvent>,
+}
+
+impl Follow {
+    /// Spawn the follow process with `config`. The timeout, retries, hooks
+    /// and stderr callback of `config` don't apply to the long-running
+    /// process.
+    pub(crate) fn spawn(config: &PlayerctlConfig) -> Result<Self> {
+        let command = config.command(&["-a", "metadata", "--follow", "--format", &follow_template()]);
+        Ok(Self::new(FollowProcess::spawn_command(command)?, config.clone()))
+    }
+
+    fn new(process: FollowProcess, config: PlayerctlConfig) -> Self {
+        Self { process, config, known: HashSet::new(), queued: VecDeque::new() }
+    }
+
+    /// Queue the events of one line of follow output.
+    fn handle_line(&mut self, line: &str) -> Result<()> {
+        match parse_line(line)? {
+            Some(PlayerEvent::Metadata { player, mut metadata }) => {
+                self.config.postprocess(&mut metadata)?;
+                if self.known.insert(player.clone()) {
+                    self.queued.push_back(PlayerEvent::PlayerAppeared(player.clone()));
+                }
+                self.queued.push_back(PlayerEvent::Metadata { player, metadata });
+            }
+            Some(event) => self.queued.push_back(event),
+            // A player exited.
+            None => {
+                let running = list_players_with(&self.config)?;
+                let mut gone: Vec<_> = self
+                    .known
+                    .iter()
+                    .filter(|player| !running.contains(player))
+                    .cloned()
+                    .collect();
+                gone.sort_unstable();
+                for player in gone {
+                    self.known.remove(&player);
+                    self.queued.push_back(PlayerEvent::PlayerDisappeared(player));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Stop following and terminate the `playerctl` process.
+    pub fn stop(mut self) -> Result<()> {
+        self.process.stop()
+    }
+}
+
+impl Iterator for Follow {
+    type Item = Result<PlayerEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(event) = self.queued.pop_front() {
+                return Some(Ok(event));
+            }
+            let line = match self.process.next_line()? {
+                Ok(line) => line,
+                Err(e) => return Some(Err(e)),
+            };
+            if let Err(e) = self.handle_line(&line) {
+                return Some(Err(e));
+            }
+        }
+    }
+}
+
+/// Iterator over the default player's metadata rendered with a `--format`
+/// template on every change, backed by a running `playerctl --follow`
+/// process.
+///
+/// Created by [`PlayerctlHandle::follow_display`](crate::PlayerctlHandle::follow_display).
+/// An empty line is yielded when the player exits. The process is terminated
+/// when the iterator is dropped.
+pub struct FormattedFollow {
+    process: FollowProcess,
+}
+
+impl FormattedFollow {
+    /// Spawn the follow process with `config`. The timeout and retries of
+    /// `config` don't apply to the long-running process.
+    pub(crate) fn spawn(template: &str, config: &PlayerctlConfig) -> Result<Self> {
+        let command = config.command(&["metadata", "--follow", "--format", template]);
+        let process = FollowProcess::spawn_command(command)?;
+        Ok(Self { process })
+    }
+
+    /// Stop following and terminate the `playerctl` process.
+    pub fn stop(mut self) -> Result<()> {
+        self.process.stop()
+    }
+}
+
+impl Iterator for FormattedFollow {
+    type Item = Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.process.next_line()
+    }
+}
+
+/// Handle of a background thread sending followed [`PlayerEvent`]s into a
+/// channel, created by [`Playerctl::drain_follow_into`].
+///
+/// Dropping the handle stops following, like [`FollowDrain::stop`].
+pub struct FollowDrain {
+    child: Arc<Mutex<Option<Child>>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl FollowDrain {
+    /// Send the events of `follow` into `tx` on a new thread.
+    pub(crate) fn start(follow: Follow, tx: Sender<PlayerEvent>) -> Self {
+        // Stop at the first error or once the receiver is gone.
+        Self::forward(follow, move |event| event.is_ok_and(|event| tx.send(event).is_ok()))
+    }
+
+    /// Pass the events of `follow` to `send` on a new thread, until it
+    /// returns `false`.
+    fn forward(mut follow: Follow, mut send: impl FnMut(Result<PlayerEvent>) -> bool + Send + 'static) -> Self {
+        let child = Arc::new(Mutex::new(follow.process.child.take()));
+        let thread = thread::spawn({
+            let child = Arc::clone(&child);
+            move || {
+                for event in follow {
+                    if !send(event) {
+                        break;
+                    }
+                }
+                let _ = kill(&child);
+            }
+        });
+        Self { child, thread: Some(thread) }
+    }
+
+    /// Whether following ended, because `playerctl` exited, failed or the
+    /// receiver was dropped.
+    pub fn is_finished(&self) -> bool {
+        self.thread.as_ref().is_none_or(JoinHandle::is_finished)
+    }
+
+    /// Stop following, terminate the `playerctl` process and wait for the
+    /// background thread to end.
+    pub fn stop(mut self) -> Result<()> {
+        self.shutdown()
+    }
+
+    fn shutdown(&mut self) -> Result<()> {
+        kill(&self.child)?;
+        if let Some(thread) = self.thread.take() {
+            thread
+                .join()
+                .map_err(|_| PlayerctlError::Other("follow thread panicked".to_owned()))?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for FollowDrain {
+    fn drop(&mut self) {
+        let _ = self.shutdown();
+    }
+}
+
+/// Iterator over [`PlayerEvent`]s of all players like [`Follow`], but
+/// coalescing bursts of changes, created by [`Playerctl::follow_debounced`].
+///
+/// Metadata events of a player arriving within the debounce window after its
+/// first one are [merged](PlayerMetadata::merge) into a single event,
+/// yielded once the window has passed. Any other event ends the window
+/// early. Appearance and disappearance events are yielded right away.
+///
+/// The `playerctl` process runs on a background thread and is terminated
+/// when the iterator is dropped.
+pub struct DebouncedFollow {
+    events: Receiver<Result<PlayerEvent>>,
+    window: Duration,
+    /// The event that ended the last window, yielded next.
+    pending: Option<Result<PlayerEvent>>,
+    drain: FollowDrain,
+}
+
+impl DebouncedFollow {
+    pub(crate) fn start(follow: Follow, window: Duration) -> Self {
+        let (tx, events) = mpsc::channel();
+        // Forward the first error too, then stop.
+        let drain = FollowDrain::forward(follow, move |event| {
+            let failed = event.is_err();
+            tx.send(event).is_ok() && !failed
+        });
+        Self { events, window, pending: None, drain }
+    }
+
+    /// Stop following and terminate the `playerctl` process.
+    pub fn stop(self) -> Result<()> {
+        self.drain.stop()
+    }
+}
+
+impl Iterator for DebouncedFollow {
+    type Item = Result<PlayerEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut event = match self.pending.take() {
+            Some(event) => event,
+            None => self.events.recv().ok()?,
+        };
+        let Ok(PlayerEvent::Metadata { player, metadata }) = &mut event else {
+            return Some(event);
+        };
+        let deadline = Instant::now() + self.window;
+        // Collect until the window passes or the follow thread ends.
+        while let Ok(next) = self.events.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            match next {
+                Ok(PlayerEvent::Metadata { player: next_player, metadata: next_metadata }) if next_player == *player => {
+                    metadata.merge(next_metadata);
+                }
+                next => {
+                    self.pending = Some(next);
+                    break;
+                }
+            }
+        }
+        Some(event)
+    }
+}
+
+/// Kill and reap the shared child, if it is still running.
+fn kill(child: &Mutex<Option<Child>>) -> Result<()> {
+    let child = child.lock().map_err(|_| PlayerctlError::Other("follow process lock poisoned".to_owned()))?.take();
+    if let Some(mut child) = child {
+        child.kill()?;
+        child.wait()?;
+    }
+    Ok(())
+}
+
+impl Playerctl {
+    /// Follow metadata changes of all players on a background thread,
+    /// sending each event into `tx`.
+    ///
+    /// This bridges following to an existing event bus. Following stops,
+    /// terminating the `playerctl` process, when the returned handle is
+    /// stopped or dropped, when `playerctl` exits or fails, or when the
+    /// receiver is dropped; the latter is only noticed at the next event.
+    ///
+    /// ```no_run
+    /// # use std::sync::mpsc;
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// let (tx, rx) = mpsc::channel();
+    /// let drain = Playerctl::drain_follow_into(tx).unwrap();
+    /// for event in rx.iter().take(3) {
+    ///     println!("{event:?}");
+    /// }
+    /// drain.stop().unwrap();
+    /// ```
+    pub fn drain_follow_into(tx: Sender<PlayerEvent>) -> Result<FollowDrain> {
+        PlayerctlHandle::default().drain_follow_into(tx)
+    }
+
+    /// Follow metadata changes of all players like [`Playerctl::follow`],
+    /// coalescing the changes a player reports within `window` into one
+    /// event, see [`DebouncedFollow`].
+    ///
+    /// Players often report a change as a burst of lines (e.g. title, then
+    /// art), so this avoids redrawing a UI for each of them, at the cost of
+    /// delaying each event by up to `window`.
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use playerctl_rust_wrapper::{PlayerEvent, Playerctl};
+    /// for event in Playerctl::follow_debounced(Duration::from_millis(100)).unwrap() {
+    ///     if let PlayerEvent::Metadata { player, metadata } = event.unwrap() {
+    ///         println!("{player}: {:?}", metadata.xesam_title);
+    ///     }
+    /// }
+    /// ```
+    pub fn follow_debounced(window: Duration) -> Result<DebouncedFollow> {
+        PlayerctlHandle::default().follow_debounced(window)
+    }
+
+    /// Follow metadata changes of all players.
+    ///
+    /// The iterator blocks until the next change and ends when `playerctl`
+    /// exits.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::{PlayerEvent, Playerctl};
+    /// for event in Playerctl::follow().unwrap() {
+    ///     if let PlayerEvent::Metadata { player, metadata } = event.unwrap() {
+    ///         println!("{player}: {:?}", metadata.xesam_title);
+    ///     }
+    /// }
+    /// ```
+    pub fn follow() -> Result<Follow> {
+        PlayerctlHandle::default().follow()
+    }
+}
+
+/// Build the `--format` template printing the player name followed by all
+/// [`FOLLOW_KEYS`].
+pub(crate) fn follow_template() -> String {
+    let mut template = String::from("{{playerName}}");
+    for key in FOLLOW_KEYS {
+        template.push(SEPARATOR);
+        template.push_str(&format!("{{{{{key}}}}}"));
+    }
+    template
+}
+
+/// Parse one line of follow output. Empty lines (printed when a player
+/// exits) yield `None`.
+fn parse_line(line: &str) -> Result<Option<PlayerEvent>> {
+    Ok(parse_fields(line)?.map(|(player, metadata)| PlayerEvent::Metadata { player, metadata }))
+}
+
+/// Parse one line printed with [`follow_template`] into the player name and
+/// its metadata. Lines without player name yield `None`.
+pub(crate) fn parse_fields(line: &str) -> Result<Option<(String, PlayerMetadata)>> {
+    let mut fields = line.split(SEPARATOR);
+    let player = fields.next().unwrap_or_default();
+    if player.is_empty() {
+        return Ok(None);
+    }
+    let mut metadata = PlayerMetadata::default();
+    for (key, val) in FOLLOW_KEYS.iter().zip(fields) {
+        if !val.is_empty() {
+            metadata.insert_value(key, val)?;
+        }
+    }
+    Ok(Some((player.to_owned(), metadata)))
+}
+
+#[cfg(feature = "json")]
+mod json {
+    use std::io::Write;
+
+    use super::PlayerEvent;
+    use crate::{PlayerMetadata, Playerctl, PlayerctlHandle, Result};
+
+    #[derive(serde::Serialize)]
+    struct JsonLine<'a> {
+        player: &'a str,
+        #[serde(flatten)]
+        metadata: &'a PlayerMetadata,
+    }
+
+    #[derive(serde::Serialize)]
+    struct JsonPresence<'a> {
+        player: &'a str,
+        event: &'a str,
+    }
+
+    impl PlayerEvent {
+        /// Render the event as a single line of JSON, without a trailing
+        /// newline.
+        ///
+        /// Metadata events hold the `player` name and its metadata;
+        /// appearance and disappearance events hold the `player` name and
+        /// an `event` of `"appeared"` or `"disappeared"`.
+        pub fn to_json(&self) -> Result<String> {
+            let presence = |player, event| serde_json::to_string(&JsonPresence { player, event });
+            match self {
+                PlayerEvent::Metadata { player, metadata } => {
+                    Ok(serde_json::to_string(&JsonLine { player, metadata })?)
+                }
+                PlayerEvent::PlayerAppeared(player) => Ok(presence(player, "appeared")?),
+                PlayerEvent::PlayerDisappeared(player) => Ok(presence(player, "disappeared")?),
+            }
+        }
+    }
+
+    impl Playerctl {
+        /// Follow metadata changes of all players as newline-delimited JSON.
+        ///
+        /// Each item is one JSON object (without trailing newline) holding
+        /// the `player` name, the typed metadata fields and the `raw` map.
+        /// Fields that are `None` are omitted. Players appearing and
+        /// disappearing are reported too, see [`PlayerEvent::to_json`].
+        ///
+        /// ```no_run
+        /// # use playerctl_rust_wrapper::Playerctl;
+        /// for line in Playerctl::metadata_stream_json().unwrap() {
+        ///     println!("{}", line.unwrap());
+        /// }
+        /// ```
+        pub fn metadata_stream_json() -> Result<impl Iterator<Item = Result<String>>> {
+            PlayerctlHandle::default().metadata_stream_json()
+        }
+
+        /// Write followed metadata changes to `out` as newline-delimited
+        /// JSON, flushing after every line. See
+        /// [`Playerctl::metadata_stream_json`] for the line format.
+        ///
+        /// This only returns once `playerctl` exits or writing fails.
+        ///
+        /// ```no_run
+        /// # use playerctl_rust_wrapper::Playerctl;
+        /// Playerctl::write_metadata_stream_json(std::io::stdout()).unwrap();
+        /// ```
+        pub fn write_metadata_stream_json(out: impl Write) -> Result<()> {
+            PlayerctlHandle::default().write_metadata_stream_json(out)
+        }
+    }
+
+    impl PlayerctlHandle {
+        /// Follow metadata changes of all players as newline-delimited JSON.
+        /// See [`Playerctl::metadata_stream_json`] and
+        /// [`follow`](Self::follow).
+        pub fn metadata_stream_json(&self) -> Result<impl Iterator<Item = Result<String>>> {
+            Ok(self.follow()?.map(|event| event?.to_json()))
+        }
+
+        /// Write followed metadata changes to `out` as newline-delimited
+        /// JSON. See [`Playerctl::write_metadata_stream_json`].
+        pub fn write_metadata_stream_json(&self, mut out: impl Write) -> Result<()> {
+            for line in self.metadata_stream_json()? {
+                writeln!(out, "{}", line?)?;
+                out.flush()?;
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{calls, mock, ok};
+    use std::io::Cursor;
+
+    fn line(fields: &[&str]) -> String {
+        fields.join(&SEPARATOR.to_string())
+    }
+
+    #[test]
+    fn template_lists_player_name_and_keys() {
+        let template = follow_template();
+        assert!(template.starts_with("{{playerName}}\u{1f}{{mpris:trackid}}"));
+        assert!(template.ends_with("{{xesam:contentCreated}}"));
+    }
+
+    /// Follow `output` as if printed by playerctl.
+    fn follow_output(output: String) -> Follow {
+        Follow::new(FollowProcess::from_reader(Cursor::new(output)), PlayerctlConfig::default())
+    }
+
+    /// The metadata events of `events`.
+    fn metadata_events(events: &[PlayerEvent]) -> Vec<(&str, &PlayerMetadata)> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                PlayerEvent::Metadata { player, metadata } => Some((player.as_str(), metadata)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn follows_lines_and_skips_empty_ones() {
+        let output = format!(
+            "{}\n\n{}\n",
+            line(&["mpv", "/1", "", "1000", "Song"]),
+            line(&["firefox", "", "", "", "Video"])
+        );
+        mock([ok("mpv\n")]);
+        let follow = follow_output(output);
+        let events: Vec<_> = follow
+            .map(|event| event.unwrap())
+            .collect();
+        let metadata_events = metadata_events(&events);
+        assert_eq!(metadata_events.len(), 2);
+        let (player, metadata) = metadata_events[0];
+        assert_eq!(player, "mpv");
+        assert_eq!(metadata.mpris_length, Some(1000));
+        assert_eq!(metadata.xesam_title.as_deref(), Some("Song"));
+        assert_eq!(metadata.mpris_art_url, None);
+        assert!(!metadata.raw.contains_key("mpris:artUrl"));
+    }
+
+    #[test]
+    fn config_applies_to_followed_metadata() {
+        let output = format!("{}\n", line(&["firefox", "", "", "", "A &amp; B"]));
+        let config = PlayerctlConfig { decode_html_entities: true, ..Default::default() };
+        let events: Vec<_> = Follow::new(FollowProcess::from_reader(Cursor::new(output)), config)
+            .map(|event| event.unwrap())
+            .collect();
+        let (_, metadata) = metadata_events(&events)[0];
+        assert_eq!(metadata.xesam_title.as_deref(), Some("A & B"));
+        assert_eq!(metadata.raw["xesam:title"], "A &amp; B");
+    }
+
+    #[test]
+    fn reports_players_appearing_and_disappearing() {
+        let output = format!(
+            "{}\n{}\n{}\n\n{}\n\n",
+            line(&["mpv", "/1"]),
+            line(&["firefox", "/2"]),
+            line(&["mpv", "/3"]),
+            line(&["firefox", "/4"]),
+        );
+        mock([ok("firefox\n"), Err(PlayerctlError::NoPlayer)]);
+        let events: Vec<_> = follow_output(output)
+            .map(|event| event.unwrap())
+            .filter(|event| !matches!(event, PlayerEvent::Metadata { .. }))
+            .collect();
+        assert_eq!(
+            events,
+            [
+                PlayerEvent::PlayerAppeared("mpv".to_owned()),
+                PlayerEvent::PlayerAppeared("firefox".to_owned()),
+                PlayerEvent::PlayerDisappeared("mpv".to_owned()),
+                PlayerEvent::PlayerDisappeared("firefox".to_owned()),
+            ]
+        );
+        assert_eq!(calls(), [["-l"], ["-l"]]);
+    }
+
+    #[test]
+    fn debounce_coalesces_bursts() {
+        let output = format!(
+            "{}\n{}\n{}\n{}\n",
+            line(&["mpv", "/1", "", "", "Song"]),
+            line(&["mpv", "/2", "file:///tmp/art.png"]),
+            line(&["mpv", "/3", "", "1000"]),
+            line(&["firefox", "", "", "", "Video"])
+        );
+        let events: Vec<_> = DebouncedFollow::start(follow_output(output), Duration::from_secs(5))
+            .map(|event| event.unwrap())
+            .collect();
+        assert_eq!(events.len(), 4);
+        assert_eq!(events[0], PlayerEvent::PlayerAppeared("mpv".to_owned()));
+        assert_eq!(events[2], PlayerEvent::PlayerAppeared("firefox".to_owned()));
+        let metadata_events = metadata_events(&events);
+        assert_eq!(metadata_events.len(), 2);
+        let (player, metadata) = metadata_events[0];
+        assert_eq!(player, "mpv");
+        assert_eq!(metadata.mpris_trackid.as_deref(), Some("/3"));
+        assert_eq!(metadata.mpris_art_url.as_deref(), Some("file:///tmp/art.png"));
+        assert_eq!(metadata.mpris_length, Some(1000));
+        assert_eq!(metadata.xesam_title.as_deref(), Some("Song"));
+        assert_eq!(metadata_events[1].0, "firefox");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn drop_terminates_child() {
+        let mut command = Command::new("sleep");
+        command.arg("60");
+        let process = FollowProcess::spawn_command(command).unwrap();
+        let pid = process.child.as_ref().unwrap().id();
+        let proc_path = format!("/proc/{pid}");
+        assert!(std::path::Path::new(&proc_path).exists());
+        drop(process);
+        assert!(!std::path::Path::new(&proc_path).exists());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn drain_stops_when_receiver_is_dropped() {
+        let mut command = Command::new("sh");
+        command.args([
+            "-c",
+            r"printf 'mpv\037/1\nmpv\037/2\n'; sleep 0.3; printf 'mpv\037/3\n'; exec sleep 60",
+        ]);
+        let process = FollowProcess::spawn_command(command).unwrap();
+        let pid = process.child.as_ref().unwrap().id();
+        let (tx, rx) = std::sync::mpsc::channel();
+        let drain = FollowDrain::start(Follow::new(process, PlayerctlConfig::default()), tx);
+        let ids: Vec<_> = rx
+            .iter()
+            .filter_map(|event| match event {
+                PlayerEvent::Metadata { metadata, .. } => metadata.mpris_trackid,
+                _ => None,
+            })
+            .take(2)
+            .collect();
+        assert_eq!(ids, ["/1", "/2"]);
+        drop(rx);
+        let start = std::time::Instant::now();
+        while !drain.is_finished() {
+            assert!(start.elapsed() < std::time::Duration::from_secs(10));
+            thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert!(!std::path::Path::new(&format!("/proc/{pid}")).exists());
+        drain.stop().unwrap();
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_omits_missing_fields() {
+        let event = parse_line(&line(&["mpv", "", "", "", "Song"])).unwrap().unwrap();
+        let json: serde_json::Value = serde_json::from_str(&event.to_json().unwrap()).unwrap();
+        assert_eq!(json["player"], "mpv");
+        assert_eq!(json["xesam_title"], "Song");
+        assert!(json.get("xesam_album").is_none());
+
+        let json = PlayerEvent::PlayerDisappeared("mpv".to_owned()).to_json().unwrap();
+        assert_eq!(json, r#"{"player":"mpv","event":"disappeared"}"#);
+    }
+}
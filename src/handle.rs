@@ -0,0 +1,1685 @@
+//! Configurable playerctl handle.
+
+use std::{
+    collections::HashMap,
+    ops::Deref,
+    path::PathBuf,
+    sync::{mpsc::Sender, Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    absolute_position, decode_value, duration_micros, end_position, fetch_each, follow, full_state_with, help_commands,
+    is_daemon, known_position_and_length, list_players_with, lists_daemon, metadata_fast_with, metadata_for_with,
+    metadata_with, mode_result, normalize_uri, parse_metadata, parse_metadata_with, parse_relative_offset,
+    parse_shuffle, parse_status, parse_volume, percent_position, ping_result, play_pause_all_command, player_capability,
+    player_matches, position_and_length, remaining_fraction_target, run_args_with, run_lines_with, run_raw_with,
+    shuffle_arg, skip_and_metadata, skip_if_supported, volume_exponent, volume_level, DebouncedFollow, Follow,
+    FollowDrain, FormattedFollow, FullState, LoopStatus, MetadataOptions, MetadataSnapshot, PlayerEvent, PlayerInstance,
+    PlayerMetadata, PlayerctlConfig, PlayerctlError, Result, SignedDuration, TrackStatus, Version, DAEMON_SHUTDOWN_POLL,
+    DAEMON_SHUTDOWN_TIMEOUT, PERCEPTUAL_VOLUME_EXPONENT, PLAY_URI_ATTEMPTS, PLAY_URI_DELAY, VERIFY_POLL_INTERVAL,
+};
+
+/// Volume restored by [`PlayerctlHandle::unmute`] if no volume was stored.
+pub const UNMUTE_DEFAULT_VOLUME: f64 = 0.5;
+
+/// Playerctl handle carrying options shared by its methods, see
+/// [`PlayerctlConfig`].
+///
+/// ```no_run
+/// # use playerctl_rust_wrapper::PlayerctlHandle;
+/// let playerctl = PlayerctlHandle::default()
+///     .with_format_default("{{status}}: {{title}}")
+///     .unwrap();
+/// println!("{}", playerctl.display(None::<&str>).unwrap());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PlayerctlHandle {
+    config: PlayerctlConfig,
+    /// Volume before muting, `Some` while muted.
+    muted_volume: Option<f64>,
+    /// Index of the player focused by [`toggle_between`](Self::toggle_between).
+    focus: Option<usize>,
+}
+
+impl PlayerctlHandle {
+    /// Set the `--format` template used by [`display`](Self::display) and
+    /// [`follow_display`](Self::follow_display) when no template is passed
+    /// to them. Defaults to [`DEFAULT_FORMAT`](crate::DEFAULT_FORMAT).
+    ///
+    /// Fails with [`PlayerctlError::InvalidArgument`] if the template is
+    /// empty.
+    pub fn with_format_default(mut self, template: impl Into<String>) -> Result<Self> {
+        let template = template.into();
+        if template.trim().is_empty() {
+            return Err(PlayerctlError::InvalidArgument(
+                "format template must not be empty".to_owned(),
+            ));
+        }
+        self.config.format_default = template;
+        Ok(self)
+    }
+
+    /// Run playerctl with `LC_ALL=C`, so its output doesn't depend on the
+    /// user's locale (e.g. decimal separators or month names).
+    ///
+    /// This also affects output that is passed through, which then loses
+    /// any localized strings.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::PlayerctlHandle;
+    /// let playerctl = PlayerctlHandle::default().with_lc_all_c();
+    /// println!("{}", playerctl.display(None::<&str>).unwrap());
+    /// ```
+    pub fn with_lc_all_c(mut self) -> Self {
+        self.config.lc_all_c = true;
+        self
+    }
+
+    /// Pass the stderr output of this handle's commands to `callback`. See
+    /// [`PlayerctlConfig::stderr_callback`].
+    pub fn with_stderr_callback(mut self, callback: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        self.config.stderr_callback = Some(Arc::new(callback));
+        self
+    }
+
+    /// Call `hook` with the arguments of every command just before it runs.
+    /// See [`PlayerctlConfig::before_command`].
+    pub fn with_before_command_hook(mut self, hook: impl Fn(&[&str]) + Send + Sync + 'static) -> Self {
+        self.config.before_command = Some(Arc::new(hook));
+        self
+    }
+
+    /// Call `hook` with the arguments, result and duration of every command
+    /// after it ran. See [`PlayerctlConfig::after_command`].
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::PlayerctlHandle;
+    /// let playerctl = PlayerctlHandle::default().with_after_command_hook(|args, result, elapsed| {
+    ///     eprintln!("playerctl {args:?} took {elapsed:?}, ok: {}", result.is_ok());
+    /// });
+    /// playerctl.play().unwrap();
+    /// ```
+    pub fn with_after_command_hook(
+        mut self,
+        hook: impl Fn(&[&str], &Result<String>, Duration) + Send + Sync + 'static,
+    ) -> Self {
+        self.config.after_command = Some(Arc::new(hook));
+        self
+    }
+
+    /// Run playerctl in `dir`, so relative paths passed to
+    /// [`open`](Self::open) resolve against it. See
+    /// [`PlayerctlConfig::working_directory`].
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::PlayerctlHandle;
+    /// let playerctl = PlayerctlHandle::default().with_working_directory("/music/playlists");
+    /// playerctl.open("../albums/01.opus").unwrap();
+    /// ```
+    pub fn with_working_directory(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.config.working_directory = Some(dir.into());
+        self
+    }
+
+    /// Run playerctl with the given niceness, so it doesn't contend with
+    /// foreground apps. Unix only, see [`PlayerctlConfig::niceness`].
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::PlayerctlHandle;
+    /// let playerctl = PlayerctlHandle::default().with_spawn_niceness(10);
+    /// println!("{:?}", playerctl.status().unwrap());
+    /// ```
+    #[cfg(unix)]
+    pub fn with_spawn_niceness(mut self, niceness: i32) -> Self {
+        self.config.niceness = Some(niceness);
+        self
+    }
+
+    /// Kill commands running longer than `timeout`. See
+    /// [`PlayerctlConfig::timeout`].
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.config.timeout = Some(timeout);
+        self
+    }
+
+    /// A view of this handle for a single call that needs a different
+    /// timeout, e.g. a short one for a one-off [`metadata`](Self::metadata)
+    /// while the handle's own timeout stays longer. `timeout` takes
+    /// precedence over the handle's [`PlayerctlConfig::timeout`]; `None`
+    /// disables the timeout. See [`CallOverride`].
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// let playerctl = Playerctl::with_timeout(Duration::from_secs(5));
+    /// let metadata = playerctl.call_timeout(Some(Duration::from_millis(200))).metadata();
+    /// ```
+    pub fn call_timeout(&self, timeout: Option<Duration>) -> CallOverride {
+        let mut config = self.config.clone();
+        config.timeout = timeout;
+        // Only the options are copied, the handle's state stays with it.
+        CallOverride { handle: PlayerctlHandle { config, ..Default::default() } }
+    }
+
+    /// Replace all options of this handle with `config`.
+    pub fn with_config(mut self, config: PlayerctlConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// The options of this handle.
+    pub fn config(&self) -> &PlayerctlConfig {
+        &self.config
+    }
+
+    /// The default `--format` template of this handle.
+    pub fn format_default(&self) -> &str {
+        &self.config.format_default
+    }
+
+    /// Get a handle bound to the current default player.
+    /// See [`Playerctl::current_player_handle`](crate::Playerctl::current_player_handle).
+    pub fn current_player_handle(&self) -> Result<PlayerInstance> {
+        let name = self.run(&["status", "-f", "{{playerInstance}}"])?;
+        if name.is_empty() {
+            return Err(PlayerctlError::NoPlayer);
+        }
+        Ok(PlayerInstance::new(name))
+    }
+
+    /// Check whether the installed playerctl supports `subcommand`, e.g. `loop`
+    /// or `shuffle`, which older versions lack.
+    /// See [`Playerctl::command_exists`](crate::Playerctl::command_exists).
+    pub fn command_exists(&self, subcommand: &str) -> Result<bool> {
+        /// The commands of each binary queried so far.
+        static COMMANDS: Mutex<Vec<(PathBuf, Vec<String>)>> = Mutex::new(Vec::new());
+        let exists = |commands: &[String]| commands.iter().any(|command| command == subcommand);
+        let binary = &self.config.binary;
+        let cached = COMMANDS.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((_, commands)) = cached.iter().find(|(cached, _)| cached == binary) {
+            return Ok(exists(commands));
+        }
+        drop(cached);
+        let commands = help_commands(&self.run_raw(&["--help"])?);
+        let result = exists(&commands);
+        COMMANDS.lock().unwrap_or_else(|e| e.into_inner()).push((binary.clone(), commands));
+        Ok(result)
+    }
+
+    /// Get the version of the installed playerctl.
+    /// See [`Playerctl::version_parsed`](crate::Playerctl::version_parsed).
+    pub fn version_parsed(&self) -> Result<Version> {
+        self.run(&["--version"])?.parse()
+    }
+
+    /// Command the player to play. See [`Playerctl::play`](crate::Playerctl::play).
+    pub fn play(&self) -> Result<()> {
+        self.run(&["play"])?;
+        Ok(())
+    }
+
+    /// Command the player to pause. See [`Playerctl::pause`](crate::Playerctl::pause).
+    pub fn pause(&self) -> Result<()> {
+        self.run(&["pause"])?;
+        Ok(())
+    }
+
+    /// Command the player to toggle between play/pause. See
+    /// [`Playerctl::play_pause`](crate::Playerctl::play_pause).
+    pub fn play_pause(&self) -> Result<()> {
+        self.run(&["play-pause"])?;
+        Ok(())
+    }
+
+    /// Toggle between play/pause and make sure the player followed, for players
+    /// that ignore commands sent too soon after they launched.
+    /// See [`Playerctl::play_pause_verified`](crate::Playerctl::play_pause_verified).
+    pub fn play_pause_verified(&self, timeout: Duration) -> Result<TrackStatus> {
+        let before = self.status()?;
+        for _ in 0..2 {
+            self.play_pause()?;
+            let deadline = Instant::now() + timeout;
+            loop {
+                let status = self.status()?;
+                if status != before {
+                    return Ok(status);
+                }
+                let now = Instant::now();
+                if now >= deadline {
+                    break;
+                }
+                thread::sleep(VERIFY_POLL_INTERVAL.min(deadline - now));
+            }
+        }
+        Err(PlayerctlError::Unavailable("the player ignored play-pause".to_owned()))
+    }
+
+    /// Toggle all players together: pause all of them if any is playing,
+    /// otherwise play all of them.
+    /// See [`Playerctl::play_pause_all`](crate::Playerctl::play_pause_all).
+    pub fn play_pause_all(&self) -> Result<()> {
+        let statuses: Vec<_> = self.run_lines(&["-a", "status"])?.iter().map(|status| parse_status(status)).collect();
+        self.run(&["-a", play_pause_all_command(&statuses)])?;
+        Ok(())
+    }
+
+    /// Command the player to stop. See [`Playerctl::stop`](crate::Playerctl::stop).
+    pub fn stop(&self) -> Result<()> {
+        self.run(&["stop"])?;
+        Ok(())
+    }
+
+    /// Command the player to skip to the next track. See
+    /// [`Playerctl::next`](crate::Playerctl::next).
+    pub fn next(&self) -> Result<()> {
+        self.run(&["next"])?;
+        Ok(())
+    }
+
+    /// Command the player to skip to the previous track. See
+    /// [`Playerctl::previous`](crate::Playerctl::previous).
+    pub fn previous(&self) -> Result<()> {
+        self.run(&["previous"])?;
+        Ok(())
+    }
+
+    /// Skip the default player to the next track, if its MPRIS `CanGoNext`
+    /// property says it can.
+    /// See [`Playerctl::next_if_supported`](crate::Playerctl::next_if_supported).
+    pub fn next_if_supported(&self) -> Result<bool> {
+        skip_if_supported(self, "next", "CanGoNext")
+    }
+
+    /// Skip the default player to the previous track, if its MPRIS
+    /// `CanGoPrevious` property says it can.
+    /// See [`Playerctl::previous_if_supported`](crate::Playerctl::previous_if_supported).
+    pub fn previous_if_supported(&self) -> Result<bool> {
+        skip_if_supported(self, "previous", "CanGoPrevious")
+    }
+
+    /// Skip to the next track and get its metadata, or `None` if no player is
+    /// running.
+    /// See [`Playerctl::next_and_metadata`](crate::Playerctl::next_and_metadata).
+    pub fn next_and_metadata(&self) -> Result<Option<PlayerMetadata>> {
+        skip_and_metadata(self, Self::next)
+    }
+
+    /// Skip to the previous track and get its metadata.
+    /// See [`Playerctl::previous_and_metadata`](crate::Playerctl::previous_and_metadata).
+    pub fn previous_and_metadata(&self) -> Result<Option<PlayerMetadata>> {
+        skip_and_metadata(self, Self::previous)
+    }
+
+    /// Command the player to seek forward/backward OFFSET in seconds.
+    /// See [`Playerctl::position`](crate::Playerctl::position).
+    pub fn position(&self, secs: f32) -> Result<()> {
+        let offset = if secs < 0. { format!("{}-", -secs) } else { format!("{secs}+") };
+        self.run(&["position", &offset])?;
+        Ok(())
+    }
+
+    /// Command the player to seek by `offset`. See
+    /// [`Playerctl::seek`](crate::Playerctl::seek).
+    pub fn seek(&self, offset: SignedDuration) -> Result<()> {
+        self.run(&["position", &offset.as_arg()])?;
+        Ok(())
+    }
+
+    /// Command the player to seek to the absolute position `secs`. See
+    /// [`Playerctl::set_position`](crate::Playerctl::set_position).
+    pub fn set_position(&self, secs: f64) -> Result<()> {
+        self.run(&["position", &absolute_position(secs)?])?;
+        Ok(())
+    }
+
+    /// Command the player `name` to seek to the absolute position `secs`.
+    /// See [`Playerctl::set_position_for`](crate::Playerctl::set_position_for).
+    pub fn set_position_for(&self, name: &str, secs: f64) -> Result<()> {
+        self.run(&["-p", name, "position", &absolute_position(secs)?])?;
+        Ok(())
+    }
+
+    /// Restart the current track if more than `threshold` has been played,
+    /// otherwise skip to the previous track, like the "previous" button of most
+    /// media players.
+    /// See [`Playerctl::smart_previous`](crate::Playerctl::smart_previous).
+    pub fn smart_previous(&self, threshold: Duration) -> Result<()> {
+        let position = match self.run(&["position"]) {
+            Ok(position) => position.parse::<f64>().ok(),
+            Err(PlayerctlError::NoPlayer) => return Err(PlayerctlError::NoPlayer),
+            Err(_) => None,
+        };
+        match position {
+            Some(position) if position > threshold.as_secs_f64() => self.set_position(0.),
+            _ => self.previous(),
+        }
+    }
+
+    /// Seek to the start of the current track.
+    /// See [`Playerctl::seek_start`](crate::Playerctl::seek_start).
+    pub fn seek_start(&self) -> Result<()> {
+        self.set_position(0.)
+    }
+
+    /// Seek to [`SEEK_END_MARGIN`](crate::SEEK_END_MARGIN) before the end of
+    /// the current track, so it doesn't overshoot into the next track.
+    /// See [`Playerctl::seek_end`](crate::Playerctl::seek_end).
+    pub fn seek_end(&self) -> Result<()> {
+        self.set_position(end_position(&[], &self.config)?)
+    }
+
+    /// Seek to the start of the current track of the player `name`.
+    /// See [`Playerctl::seek_start_for`](crate::Playerctl::seek_start_for).
+    pub fn seek_start_for(&self, name: &str) -> Result<()> {
+        self.set_position_for(name, 0.)
+    }
+
+    /// Seek to near the end of the current track of the player `name`.
+    /// See [`Playerctl::seek_end_for`](crate::Playerctl::seek_end_for).
+    pub fn seek_end_for(&self, name: &str) -> Result<()> {
+        self.set_position_for(name, end_position(&["-p", name], &self.config)?)
+    }
+
+    /// Seek forward by the fraction `frac` of the remaining time, e.g. `0.1` to
+    /// skip 10% of what's left.
+    /// See [`Playerctl::seek_by_remaining_fraction`](crate::Playerctl::seek_by_remaining_fraction).
+    pub fn seek_by_remaining_fraction(&self, frac: f32) -> Result<()> {
+        if frac.is_nan() {
+            return Err(PlayerctlError::InvalidArgument("fraction must be a number".to_owned()));
+        }
+        let (position, length) = known_position_and_length(&[], &self.config)?;
+        let target = remaining_fraction_target(position, length, frac);
+        self.set_position(Duration::from_micros(target).as_secs_f64())
+    }
+
+    /// Seek to the fraction `frac` of the current track, e.g. `0.5` for the
+    /// middle.
+    /// See [`Playerctl::seek_to_percent`](crate::Playerctl::seek_to_percent).
+    pub fn seek_to_percent(&self, frac: f32) -> Result<()> {
+        self.set_position(percent_position(&[], frac, &self.config)?)
+    }
+
+    /// Seek to the fraction `frac` of the current track of the player `name`.
+    /// See [`Playerctl::seek_to_percent_for`](crate::Playerctl::seek_to_percent_for).
+    pub fn seek_to_percent_for(&self, name: &str, frac: f32) -> Result<()> {
+        self.set_position_for(name, percent_position(&["-p", name], frac, &self.config)?)
+    }
+
+    /// Skip forward by one "chapter" of fixed length `chapter_len`, without
+    /// going past the end of the track.
+    /// See [`Playerctl::chapter_skip_forward`](crate::Playerctl::chapter_skip_forward).
+    pub fn chapter_skip_forward(&self, chapter_len: Duration) -> Result<()> {
+        let (position, length) = known_position_and_length(&[], &self.config)?;
+        let target = position.saturating_add(duration_micros(chapter_len)).min(length);
+        self.set_position(Duration::from_micros(target).as_secs_f64())
+    }
+
+    /// Skip backward by one "chapter" of fixed length `chapter_len`, without
+    /// going before the start of the track.
+    /// See [`Playerctl::chapter_skip_backward`](crate::Playerctl::chapter_skip_backward).
+    pub fn chapter_skip_backward(&self, chapter_len: Duration) -> Result<()> {
+        let (position, _) = known_position_and_length(&[], &self.config)?;
+        let target = position.saturating_sub(duration_micros(chapter_len));
+        self.set_position(Duration::from_micros(target).as_secs_f64())
+    }
+
+    /// Seek forward/backward by a signed offset such as `"+1:30"`, `"-0:45"` or
+    /// `"+10"`.
+    /// See [`Playerctl::seek_relative_str`](crate::Playerctl::seek_relative_str).
+    pub fn seek_relative_str(&self, offset: &str) -> Result<()> {
+        self.position(parse_relative_offset(offset)?)
+    }
+
+    /// Command the player to open `uri`, turning local paths into `file://`
+    /// URLs. See [`Playerctl::open`](crate::Playerctl::open). Relative
+    /// paths resolve against the
+    /// [`working_directory`](PlayerctlConfig::working_directory), if set.
+    pub fn open(&self, uri: &str) -> Result<()> {
+        self.open_with(uri, true)
+    }
+
+    /// Command the player to open `uri`, turning local paths into `file://`
+    /// URLs like [`open`](Self::open) if `normalize` is set, or passing `uri`
+    /// exactly as given otherwise.
+    /// See [`Playerctl::open_with`](crate::Playerctl::open_with).
+    pub fn open_with(&self, uri: &str, normalize: bool) -> Result<()> {
+        let uri = if normalize {
+            normalize_uri(uri, self.config.working_directory.as_deref())
+        } else {
+            uri.to_owned()
+        };
+        self.run(&["open", &uri])?;
+        Ok(())
+    }
+
+    /// Open `uri` and start playing it.
+    /// See [`Playerctl::play_uri`](crate::Playerctl::play_uri).
+    pub fn play_uri(&self, uri: &str) -> Result<()> {
+        self.open(uri)?;
+        for _ in 0..PLAY_URI_ATTEMPTS {
+            thread::sleep(PLAY_URI_DELAY);
+            self.play()?;
+            if self.status()? == TrackStatus::Playing {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Open several URIs in order, e.g. to queue up a folder.
+    /// See [`Playerctl::open_many`](crate::Playerctl::open_many).
+    pub fn open_many(&self, uris: &[&str]) -> Result<()> {
+        for (opened, uri) in uris.iter().enumerate() {
+            self.open(uri).map_err(|e| PlayerctlError::OpenFailed {
+                opened,
+                total: uris.len(),
+                source: Box::new(e),
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Get current player positions, in microseconds.
+    /// See [`Playerctl::get_position`](crate::Playerctl::get_position).
+    pub fn get_position(&self) -> Result<HashMap<String, u64>> {
+        let mut m = HashMap::new();
+        for line in self.run_lines(&["status", "-a", "-f", "{{playerName}};-;{{position}}"])? {
+            let Some((name, pos)) = line.split_once(";-;") else {
+                continue;
+            };
+            m.insert(name.to_owned(), pos.parse()?);
+        }
+        Ok(m)
+    }
+
+    /// Get the position of the player `name`.
+    /// See [`Playerctl::get_position_for`](crate::Playerctl::get_position_for).
+    pub fn get_position_for(&self, name: &str) -> Result<Option<Duration>> {
+        let output = match self.run(&["-p", name, "status", "-f", "{{position}}"]) {
+            Ok(output) => output,
+            Err(PlayerctlError::NoPlayer) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        if output.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Duration::from_micros(output.parse()?)))
+    }
+
+    /// Get the time remaining in the current track of `player`.
+    /// See [`Playerctl::remaining`](crate::Playerctl::remaining).
+    pub fn remaining(&self, player: &str) -> Result<Option<Duration>> {
+        let (position, length) = position_and_length(player, &self.config)?;
+        let (Some(position), Some(length)) = (position, length) else {
+            return Ok(None);
+        };
+        Ok(Some(Duration::from_micros(length.saturating_sub(position))))
+    }
+
+    /// Get the progress through the current track of `player`, from 0.0 to 1.0.
+    /// See [`Playerctl::position_fraction`](crate::Playerctl::position_fraction).
+    pub fn position_fraction(&self, player: &str) -> Result<Option<f32>> {
+        let (position, length) = position_and_length(player, &self.config)?;
+        let (Some(position), Some(length)) = (position, length) else {
+            return Ok(None);
+        };
+        if length == 0 {
+            return Ok(None);
+        }
+        Ok(Some((position as f64 / length as f64).clamp(0., 1.) as f32))
+    }
+
+    /// Get the range `player` can seek in, from zero to the track length, e.g.
+    /// for the bounds of a scrub bar.
+    /// See [`Playerctl::seek_range`](crate::Playerctl::seek_range).
+    pub fn seek_range(&self, player: &str) -> Result<Option<(Duration, Duration)>> {
+        let (_, length) = position_and_length(player, &self.config)?;
+        Ok(length.map(|length| (Duration::ZERO, Duration::from_micros(length))))
+    }
+
+    /// Get the status, position and metadata of `player` with a single call.
+    /// See [`Playerctl::full_state`](crate::Playerctl::full_state) and
+    /// [`metadata`](Self::metadata).
+    pub fn full_state(&self, player: &str) -> Result<FullState> {
+        full_state_with(player, &self.config)
+    }
+
+    /// Set the volume to LEVEL from 0.0 to 1.0.
+    /// See [`Playerctl::volume`](crate::Playerctl::volume).
+    pub fn volume(&self, percent: f32) -> Result<()> {
+        let offset = if percent < 0. { format!("{}-", -percent) } else { format!("{percent}+") };
+        self.run(&["volume", &offset])?;
+        Ok(())
+    }
+
+    /// Get the volume of the player, from 0.0 to 1.0.
+    /// See [`Playerctl::get_volume`](crate::Playerctl::get_volume).
+    pub fn get_volume(&self) -> Result<f64> {
+        parse_volume(&self.run(&["volume"])?)
+    }
+
+    /// Set the volume to the absolute `level`, clamped to 0.0 to 1.0.
+    /// See [`Playerctl::set_volume`](crate::Playerctl::set_volume).
+    pub fn set_volume(&self, level: f64) -> Result<()> {
+        self.run(&["volume", &volume_level(level)?])?;
+        Ok(())
+    }
+
+    /// Get the volume of the player on a perceptual scale from 0.0 to 1.0.
+    /// See [`Playerctl::get_volume_perceptual`](crate::Playerctl::get_volume_perceptual).
+    pub fn get_volume_perceptual(&self) -> Result<f64> {
+        self.get_volume_curve(PERCEPTUAL_VOLUME_EXPONENT)
+    }
+
+    /// Set the volume from a perceptual `level` from 0.0 to 1.0, e.g. the
+    /// position of a volume slider, which feels more even than a linear volume.
+    /// See [`Playerctl::set_volume_perceptual`](crate::Playerctl::set_volume_perceptual).
+    pub fn set_volume_perceptual(&self, level: f64) -> Result<()> {
+        self.set_volume_curve(level, PERCEPTUAL_VOLUME_EXPONENT)
+    }
+
+    /// Get the volume of the player as `volume^(1 / exponent)`, the inverse of
+    /// [`set_volume_curve`](Self::set_volume_curve).
+    /// See [`Playerctl::get_volume_curve`](crate::Playerctl::get_volume_curve).
+    pub fn get_volume_curve(&self, exponent: f64) -> Result<f64> {
+        let exponent = volume_exponent(exponent)?;
+        Ok(self.get_volume()?.clamp(0., 1.).powf(exponent.recip()))
+    }
+
+    /// Set the volume to `level^exponent`, with `level` clamped to 0.0 to 1.0.
+    /// See [`Playerctl::set_volume_curve`](crate::Playerctl::set_volume_curve).
+    pub fn set_volume_curve(&self, level: f64, exponent: f64) -> Result<()> {
+        let exponent = volume_exponent(exponent)?;
+        if level.is_nan() {
+            return Err(PlayerctlError::InvalidArgument("volume must be a number".to_owned()));
+        }
+        self.set_volume(level.clamp(0., 1.).powf(exponent))
+    }
+
+    /// Get the volume of the player `name`, from 0.0 to 1.0.
+    /// See [`Playerctl::get_volume_for`](crate::Playerctl::get_volume_for).
+    pub fn get_volume_for(&self, name: &str) -> Result<f64> {
+        parse_volume(&self.run(&["-p", name, "volume"])?)
+    }
+
+    /// Set the volume of the player `name` to the absolute `level`, clamped to
+    /// 0.0 to 1.0.
+    /// See [`Playerctl::set_volume_for`](crate::Playerctl::set_volume_for).
+    pub fn set_volume_for(&self, name: &str, level: f64) -> Result<()> {
+        self.run(&["-p", name, "volume", &volume_level(level)?])?;
+        Ok(())
+    }
+
+    /// Change the volume of the player `name` by `delta`, e.g. `-0.1` to lower
+    /// it by 10%.
+    /// See [`Playerctl::adjust_volume_for`](crate::Playerctl::adjust_volume_for).
+    pub fn adjust_volume_for(&self, name: &str, delta: f64) -> Result<()> {
+        if delta.is_nan() {
+            return Err(PlayerctlError::InvalidArgument("volume must be a number".to_owned()));
+        }
+        let offset = if delta < 0. { format!("{}-", -delta) } else { format!("{delta}+") };
+        self.run(&["-p", name, "volume", &offset])?;
+        Ok(())
+    }
+
+    /// List the names of all running players.
+    /// See [`Playerctl::list_players`](crate::Playerctl::list_players).
+    pub fn list_players(&self) -> Result<Vec<String>> {
+        list_players_with(&self.config)
+    }
+
+    /// List all running players with their status, in playerctl's order, using
+    /// a single playerctl call instead of one per player.
+    /// See [`Playerctl::list_players_with_status`](crate::Playerctl::list_players_with_status).
+    pub fn list_players_with_status(&self) -> Result<Vec<(String, TrackStatus)>> {
+        let lines = match self.run_lines(&["status", "-a", "-f", "{{playerName}};{{status}}"]) {
+            Ok(lines) => lines,
+            Err(PlayerctlError::NoPlayer) => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        Ok(lines
+            .iter()
+            .filter_map(|line| line.rsplit_once(';'))
+            .map(|(player, status)| (player.to_owned(), parse_status(status)))
+            .collect())
+    }
+
+    /// Wait until a player is running and return its name, checking every
+    /// `poll`.
+    /// See [`Playerctl::wait_for_player`](crate::Playerctl::wait_for_player).
+    pub fn wait_for_player(&self, timeout: Duration, poll: Duration) -> Result<Option<String>> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(player) = self.running_players()?.into_iter().next() {
+                return Ok(Some(player));
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(None);
+            }
+            thread::sleep(poll.min(deadline - now));
+        }
+    }
+
+    /// Check that playerctl and the session bus respond, failing with
+    /// [`PlayerctlError::Timeout`] if the handle has a timeout and it
+    /// expires. See [`Playerctl::ping`](crate::Playerctl::ping).
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use playerctl_rust_wrapper::{Playerctl, PlayerctlConfig};
+    /// let playerctl = Playerctl::with_config(PlayerctlConfig {
+    ///     timeout: Some(Duration::from_secs(1)),
+    ///     ..Default::default()
+    /// });
+    /// playerctl.ping().unwrap();
+    /// ```
+    pub fn ping(&self) -> Result<()> {
+        ping_result(self.run(&["-l"]))
+    }
+
+    /// Stop the `playerctld` daemon by running `playerctld shutdown`, and wait
+    /// up to [`DAEMON_SHUTDOWN_TIMEOUT`](crate::DAEMON_SHUTDOWN_TIMEOUT) for it
+    /// to leave the player list, failing with [`PlayerctlError::Timeout`] if it
+    /// doesn't.
+    /// See [`Playerctl::shutdown_daemon`](crate::Playerctl::shutdown_daemon).
+    pub fn shutdown_daemon(&self) -> Result<()> {
+        if !self.using_daemon()? {
+            return Ok(());
+        }
+        let config = PlayerctlConfig {
+            binary: PathBuf::from("playerctld"),
+            ..self.config.clone()
+        };
+        if let Err(e) = run_args_with(&["shutdown"], &config) {
+            // The daemon may have exited by itself before we asked it to.
+            return if self.using_daemon()? { Err(e) } else { Ok(()) };
+        }
+        let deadline = Instant::now() + DAEMON_SHUTDOWN_TIMEOUT;
+        loop {
+            if !self.using_daemon()? {
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                return Err(PlayerctlError::Timeout(DAEMON_SHUTDOWN_TIMEOUT));
+            }
+            thread::sleep(DAEMON_SHUTDOWN_POLL);
+        }
+    }
+
+    /// Check whether commands are routed through the `playerctld` daemon.
+    /// See [`Playerctl::using_daemon`](crate::Playerctl::using_daemon).
+    pub fn using_daemon(&self) -> Result<bool> {
+        Ok(lists_daemon(&self.list_players()?))
+    }
+
+    /// Get the number of running players, not counting the `playerctld`
+    /// proxy.
+    /// See [`Playerctl::active_player_count`](crate::Playerctl::active_player_count).
+    pub fn active_player_count(&self) -> Result<usize> {
+        Ok(self.running_players()?.len())
+    }
+
+    /// Check whether a player with the given name is running.
+    /// See [`Playerctl::player_exists`](crate::Playerctl::player_exists).
+    pub fn player_exists(&self, name: &str) -> Result<bool> {
+        Ok(self.list_players()?.iter().any(|player| player_matches(player, name)))
+    }
+
+    /// Check whether `player` supports seeking, from its MPRIS `CanSeek`
+    /// property.
+    /// See [`Playerctl::seek_supported`](crate::Playerctl::seek_supported).
+    pub fn seek_supported(&self, player: &str) -> Result<bool> {
+        let players = self.running_players()?;
+        let player = players.iter().find(|name| player_matches(name, player)).ok_or(PlayerctlError::NoPlayer)?;
+        player_capability(player, "CanSeek", &self.config)
+    }
+
+    /// Get the player status. See [`Playerctl::status`](crate::Playerctl::status).
+    pub fn status(&self) -> Result<TrackStatus> {
+        Ok(parse_status(&self.run(&["status"])?))
+    }
+
+    /// Get the play status exactly as printed by playerctl, e.g. for logging
+    /// states [`TrackStatus`] doesn't know.
+    /// See [`Playerctl::status_text`](crate::Playerctl::status_text).
+    pub fn status_text(&self) -> Result<String> {
+        self.run(&["status"])
+    }
+
+    /// Get the loop status of the player.
+    /// See [`Playerctl::get_loop`](crate::Playerctl::get_loop).
+    pub fn get_loop(&self) -> Result<LoopStatus> {
+        self.run(&["loop"])?.parse()
+    }
+
+    /// Set the loop status of the player.
+    /// See [`Playerctl::set_loop`](crate::Playerctl::set_loop).
+    pub fn set_loop(&self, status: LoopStatus) -> Result<()> {
+        self.run(&["loop", status.as_str()])?;
+        Ok(())
+    }
+
+    /// Get the loop status of the player `name`.
+    /// See [`Playerctl::get_loop_for`](crate::Playerctl::get_loop_for).
+    pub fn get_loop_for(&self, name: &str) -> Result<LoopStatus> {
+        mode_result(name, "looping", self.run(&["-p", name, "loop"]), &self.config)?.parse()
+    }
+
+    /// Set the loop status of the player `name`.
+    /// See [`Playerctl::set_loop_for`](crate::Playerctl::set_loop_for).
+    pub fn set_loop_for(&self, name: &str, status: LoopStatus) -> Result<()> {
+        mode_result(name, "looping", self.run(&["-p", name, "loop", status.as_str()]), &self.config)?;
+        Ok(())
+    }
+
+    /// Set the loop status of every player.
+    /// See [`Playerctl::set_loop_all`](crate::Playerctl::set_loop_all).
+    pub fn set_loop_all(&self, status: LoopStatus) -> Result<()> {
+        self.run(&["-a", "loop", status.as_str()])?;
+        Ok(())
+    }
+
+    /// Get whether the player shuffles.
+    /// See [`Playerctl::get_shuffle`](crate::Playerctl::get_shuffle).
+    pub fn get_shuffle(&self) -> Result<bool> {
+        parse_shuffle(&self.run(&["shuffle"])?)
+    }
+
+    /// Turn shuffle on or off.
+    /// See [`Playerctl::set_shuffle`](crate::Playerctl::set_shuffle).
+    pub fn set_shuffle(&self, shuffle: bool) -> Result<()> {
+        self.run(&["shuffle", shuffle_arg(shuffle)])?;
+        Ok(())
+    }
+
+    /// Get whether the player `name` shuffles.
+    /// See [`Playerctl::get_shuffle_for`](crate::Playerctl::get_shuffle_for).
+    pub fn get_shuffle_for(&self, name: &str) -> Result<bool> {
+        parse_shuffle(&mode_result(name, "shuffling", self.run(&["-p", name, "shuffle"]), &self.config)?)
+    }
+
+    /// Turn shuffle on or off for the player `name`, leaving other players
+    /// alone.
+    /// See [`Playerctl::set_shuffle_for`](crate::Playerctl::set_shuffle_for).
+    pub fn set_shuffle_for(&self, name: &str, shuffle: bool) -> Result<()> {
+        mode_result(name, "shuffling", self.run(&["-p", name, "shuffle", shuffle_arg(shuffle)]), &self.config)?;
+        Ok(())
+    }
+
+    /// Toggle shuffle for the player `name` and return whether it shuffles now.
+    /// See [`Playerctl::toggle_shuffle_for`](crate::Playerctl::toggle_shuffle_for).
+    pub fn toggle_shuffle_for(&self, name: &str) -> Result<bool> {
+        let shuffle = !self.get_shuffle_for(name)?;
+        self.set_shuffle_for(name, shuffle)?;
+        Ok(shuffle)
+    }
+
+    /// Turn shuffle on or off for every player.
+    /// See [`Playerctl::set_shuffle_all`](crate::Playerctl::set_shuffle_all).
+    pub fn set_shuffle_all(&self, shuffle: bool) -> Result<()> {
+        self.run(&["-a", "shuffle", shuffle_arg(shuffle)])?;
+        Ok(())
+    }
+
+    /// Get the play status of the player, or `None` if no player is running.
+    /// See [`Playerctl::status_or_none`](crate::Playerctl::status_or_none).
+    pub fn status_or_none(&self) -> Result<Option<TrackStatus>> {
+        match self.status() {
+            Ok(status) => Ok(Some(status)),
+            Err(PlayerctlError::NoPlayer) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Check whether the player is playing.
+    /// See [`Playerctl::is_playing`](crate::Playerctl::is_playing).
+    pub fn is_playing(&self) -> Result<bool> {
+        Ok(self.status_or_none()?.is_some_and(TrackStatus::is_playing))
+    }
+
+    /// Check whether the player is paused.
+    /// See [`Playerctl::is_paused`](crate::Playerctl::is_paused).
+    pub fn is_paused(&self) -> Result<bool> {
+        Ok(self.status_or_none()?.is_some_and(TrackStatus::is_paused))
+    }
+
+    /// Make sure the player is playing, only sending `play` if it isn't
+    /// already.
+    /// See [`Playerctl::ensure_playing`](crate::Playerctl::ensure_playing).
+    pub fn ensure_playing(&self) -> Result<()> {
+        if self.status()? != TrackStatus::Playing {
+            self.play()?;
+        }
+        Ok(())
+    }
+
+    /// Make sure the player is not playing, only sending `pause` if it is
+    /// currently playing.
+    /// See [`Playerctl::ensure_paused`](crate::Playerctl::ensure_paused).
+    pub fn ensure_paused(&self) -> Result<()> {
+        if self.status()? == TrackStatus::Playing {
+            self.pause()?;
+        }
+        Ok(())
+    }
+
+    /// Seek to the absolute position `secs` and make sure the player is
+    /// playing.
+    /// See [`Playerctl::seek_and_play`](crate::Playerctl::seek_and_play).
+    pub fn seek_and_play(&self, secs: f64) -> Result<()> {
+        self.set_position(secs)?;
+        self.ensure_playing()
+    }
+
+    /// Seek to the absolute position `secs`, keeping the player playing or
+    /// paused as it was before.
+    /// See [`Playerctl::seek_and_keep_state`](crate::Playerctl::seek_and_keep_state).
+    pub fn seek_and_keep_state(&self, secs: f64) -> Result<()> {
+        let position = absolute_position(secs)?;
+        let was_playing = self.status()?.is_playing();
+        self.run(&["position", &position])?;
+        if was_playing {
+            self.ensure_playing()
+        } else {
+            self.ensure_paused()
+        }
+    }
+
+    /// Pause the player while running `f`, e.g. to play a notification sound,
+    /// and resume it afterwards.
+    /// See [`Playerctl::with_paused`](crate::Playerctl::with_paused).
+    pub fn with_paused<R>(&self, f: impl FnOnce() -> R) -> Result<R> {
+        let playing = match self.status() {
+            Ok(status) => status == TrackStatus::Playing,
+            Err(PlayerctlError::NoPlayer) => false,
+            Err(e) => return Err(e),
+        };
+        if !playing {
+            return Ok(f());
+        }
+
+        /// Resumes playback when dropped, unless disarmed.
+        struct ResumeGuard<'a> {
+            handle: &'a PlayerctlHandle,
+            armed: bool,
+        }
+        impl Drop for ResumeGuard<'_> {
+            fn drop(&mut self) {
+                if self.armed {
+                    let _ = self.handle.play();
+                }
+            }
+        }
+
+        self.pause()?;
+        let mut guard = ResumeGuard { handle: self, armed: true };
+        let result = f();
+        guard.armed = false;
+        self.play()?;
+        Ok(result)
+    }
+
+    /// Render the current track's metadata with a `--format` template.
+    /// See [`Playerctl::format`](crate::Playerctl::format).
+    pub fn format(&self, template: impl AsRef<str>) -> Result<String> {
+        self.run(&["metadata", "--format", template.as_ref()])
+    }
+
+    /// Render `template` for every player in a single playerctl call, keyed by
+    /// player name.
+    /// See [`Playerctl::format_all`](crate::Playerctl::format_all).
+    pub fn format_all(&self, template: impl AsRef<str>) -> Result<HashMap<String, String>> {
+        let template = format!("{{{{playerName}}}}{}{}", follow::SEPARATOR, template.as_ref());
+        let lines = match self.run_lines(&["-a", "metadata", "--format", &template]) {
+            Ok(lines) => lines,
+            Err(PlayerctlError::NoPlayer) => return Ok(HashMap::new()),
+            Err(e) => return Err(e),
+        };
+        Ok(lines
+            .iter()
+            .filter_map(|line| line.split_once(follow::SEPARATOR))
+            .map(|(player, text)| (player.to_owned(), text.to_owned()))
+            .collect())
+    }
+
+    /// Render the current track with `template` in a single playerctl call,
+    /// e.g. for a status bar.
+    /// See [`Playerctl::now_playing_line`](crate::Playerctl::now_playing_line).
+    pub fn now_playing_line(&self, template: impl AsRef<str>) -> Result<String> {
+        self.now_playing_line_or(template, "")
+    }
+
+    /// Like [`now_playing_line`](Self::now_playing_line), but returns
+    /// `fallback` if no player is running.
+    /// See [`Playerctl::now_playing_line_or`](crate::Playerctl::now_playing_line_or).
+    pub fn now_playing_line_or(&self, template: impl AsRef<str>, fallback: &str) -> Result<String> {
+        match self.format(template) {
+            Err(PlayerctlError::NoPlayer) => Ok(fallback.to_owned()),
+            result => result,
+        }
+    }
+
+    /// Get metadata of all active players as generic maps, keyed by player name
+    /// and then metadata key (e.g. `xesam:title`).
+    /// See [`Playerctl::metadata_as_map_of_maps`](crate::Playerctl::metadata_as_map_of_maps).
+    pub fn metadata_as_map_of_maps(&self) -> Result<HashMap<String, HashMap<String, String>>> {
+        parse_metadata(&self.run_raw(&["metadata", "-a"])?)?
+            .into_iter()
+            .map(|(player, metadata)| {
+                let values = metadata
+                    .raw
+                    .iter()
+                    .map(|(key, val)| Ok((key.clone(), decode_value(key, val)?)))
+                    .collect::<Result<_>>()?;
+                Ok((player, values))
+            })
+            .collect()
+    }
+
+    /// Get metadata of all active players. See
+    /// [`Playerctl::metadata`](crate::Playerctl::metadata) and
+    /// [`PlayerctlConfig::decode_raw_urls`],
+    /// [`PlayerctlConfig::decode_html_entities`],
+    /// [`PlayerctlConfig::normalize_whitespace`] and
+    /// [`PlayerctlConfig::fix_nanosecond_lengths`].
+    pub fn metadata(&self) -> Result<HashMap<String, PlayerMetadata>> {
+        metadata_with(&self.config)
+    }
+
+    /// Get metadata information for all active players like
+    /// [`metadata`](Self::metadata), stamped with the time it was fetched, for
+    /// caches and UIs deciding whether it is stale.
+    /// See [`Playerctl::metadata_snapshot`](crate::Playerctl::metadata_snapshot).
+    pub fn metadata_snapshot(&self) -> Result<MetadataSnapshot> {
+        let players = self.metadata()?;
+        Ok(MetadataSnapshot { fetched_at: Instant::now(), players })
+    }
+
+    /// Get metadata information for all active players like
+    /// [`metadata`](Self::metadata), only populating the typed fields listed in
+    /// `options`.
+    /// See [`Playerctl::metadata_with_options`](crate::Playerctl::metadata_with_options).
+    pub fn metadata_with_options(&self, options: &MetadataOptions) -> Result<HashMap<String, PlayerMetadata>> {
+        let mut metadata = parse_metadata_with(&self.run_raw(&["metadata", "-a"])?, options, None)?;
+        for player in metadata.values_mut() {
+            self.config.postprocess(player)?;
+        }
+        Ok(metadata)
+    }
+
+    /// Get metadata information for all active players like
+    /// [`metadata`](Self::metadata), but skipping values that fail to parse
+    /// instead of failing the whole call.
+    /// See [`Playerctl::metadata_with_warnings`](crate::Playerctl::metadata_with_warnings).
+    pub fn metadata_with_warnings(&self) -> Result<(HashMap<String, PlayerMetadata>, Vec<String>)> {
+        let mut warnings = Vec::new();
+        let output = self.run_raw(&["metadata", "-a"])?;
+        let mut metadata = parse_metadata_with(&output, &MetadataOptions::default(), Some(&mut warnings))?;
+        for player in metadata.values_mut() {
+            self.config.postprocess(player)?;
+        }
+        Ok((metadata, warnings))
+    }
+
+    /// Get the typed metadata fields of all active players. See
+    /// [`Playerctl::metadata_fast`](crate::Playerctl::metadata_fast) and
+    /// [`metadata`](Self::metadata).
+    pub fn metadata_fast(&self) -> Result<HashMap<String, PlayerMetadata>> {
+        metadata_fast_with(&self.config)
+    }
+
+    /// Get the metadata of the player `name`. See
+    /// [`Playerctl::metadata_for`](crate::Playerctl::metadata_for) and
+    /// [`metadata`](Self::metadata).
+    pub fn metadata_for(&self, name: &str) -> Result<PlayerMetadata> {
+        metadata_for_with(name, &self.config)
+    }
+
+    /// Get the metadata of the default player, failing with
+    /// [`PlayerctlError::NoPlayer`] if no player is running.
+    /// See [`Playerctl::metadata_current`](crate::Playerctl::metadata_current).
+    pub fn metadata_current(&self) -> Result<PlayerMetadata> {
+        let player = self.current_player_handle()?;
+        self.metadata_for(player.name())
+    }
+
+    /// Get the metadata of each of the players `names`, like
+    /// [`metadata_for`](Self::metadata_for), fetching up to `threads` players
+    /// at once.
+    /// See [`Playerctl::metadata_for_many`](crate::Playerctl::metadata_for_many).
+    pub fn metadata_for_many(&self, names: &[&str], threads: usize) -> HashMap<String, Result<PlayerMetadata>> {
+        fetch_each(names, threads, |name| self.metadata_for(name))
+    }
+
+    /// Get the metadata of the first running player of `players`, in order of
+    /// priority.
+    /// See [`Playerctl::first_available_metadata`](crate::Playerctl::first_available_metadata).
+    pub fn first_available_metadata(&self, players: &[&str]) -> Result<Option<PlayerMetadata>> {
+        if players.is_empty() {
+            return Ok(None);
+        }
+        if let Some(name) = players.iter().find(|name| name.contains(',')) {
+            return Err(PlayerctlError::InvalidArgument(format!(
+                "player name must not contain a comma: {name}"
+            )));
+        }
+        let output = match self.run_raw(&["-p", &players.join(","), "metadata"]) {
+            Ok(output) => output,
+            Err(PlayerctlError::NoPlayer) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let mut metadata = parse_metadata(&output)?.into_values().next();
+        if let Some(metadata) = &mut metadata {
+            self.config.postprocess(metadata)?;
+        }
+        Ok(metadata)
+    }
+
+    /// Follow metadata changes of all players. See
+    /// [`Playerctl::follow`](crate::Playerctl::follow).
+    ///
+    /// The metadata options of [`metadata`](Self::metadata) apply to the
+    /// events. The timeout, retries, hooks and stderr callback don't apply
+    /// to the long-running `playerctl` process.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::{PlayerEvent, Playerctl};
+    /// let playerctl = Playerctl::with_lc_all_c();
+    /// for event in playerctl.follow().unwrap() {
+    ///     println!("{:?}", event.unwrap());
+    /// }
+    /// ```
+    pub fn follow(&self) -> Result<Follow> {
+        Follow::spawn(&self.config)
+    }
+
+    /// Follow metadata changes of all players, coalescing bursts. See
+    /// [`Playerctl::follow_debounced`](crate::Playerctl::follow_debounced)
+    /// and [`follow`](Self::follow).
+    pub fn follow_debounced(&self, window: Duration) -> Result<DebouncedFollow> {
+        Ok(DebouncedFollow::start(self.follow()?, window))
+    }
+
+    /// Follow metadata changes of all players on a background thread. See
+    /// [`Playerctl::drain_follow_into`](crate::Playerctl::drain_follow_into)
+    /// and [`follow`](Self::follow).
+    pub fn drain_follow_into(&self, tx: Sender<PlayerEvent>) -> Result<FollowDrain> {
+        Ok(FollowDrain::start(self.follow()?, tx))
+    }
+
+    /// Render the current track's metadata.
+    ///
+    /// A `template` passed here, as string or
+    /// [`FormatTemplate`](crate::FormatTemplate), takes precedence over the
+    /// handle's default template. Pass `None::<&str>` for the default.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// let playerctl = Playerctl::with_format_default("{{title}}").unwrap();
+    /// println!("{}", playerctl.display(None::<&str>).unwrap());
+    /// println!("{}", playerctl.display(Some("{{album}}")).unwrap());
+    /// ```
+    pub fn display(&self, template: Option<impl AsRef<str>>) -> Result<String> {
+        let template = template.as_ref().map_or(self.config.format_default.as_str(), AsRef::as_ref);
+        self.run(&["metadata", "--format", template])
+    }
+
+    /// Follow the current track's metadata, rendering it on every change.
+    ///
+    /// As with [`display`](Self::display), a `template` passed here takes
+    /// precedence over the handle's default template.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// let playerctl = Playerctl::with_format_default("{{title}}").unwrap();
+    /// for line in playerctl.follow_display(None::<&str>).unwrap() {
+    ///     println!("{}", line.unwrap());
+    /// }
+    /// ```
+    pub fn follow_display(&self, template: Option<impl AsRef<str>>) -> Result<FormattedFollow> {
+        let template = template.as_ref().map_or(self.config.format_default.as_str(), AsRef::as_ref);
+        FormattedFollow::spawn(template, &self.config)
+    }
+
+    /// Mute the player by storing its volume in this handle and setting the
+    /// volume to 0. Does nothing if the handle already muted the player.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::PlayerctlHandle;
+    /// let mut playerctl = PlayerctlHandle::default();
+    /// playerctl.mute().unwrap();
+    /// playerctl.unmute().unwrap();
+    /// ```
+    pub fn mute(&mut self) -> Result<()> {
+        if self.muted_volume.is_some() {
+            return Ok(());
+        }
+        let volume = parse_volume(&self.run(&["volume"])?)?;
+        self.run(&["volume", "0"])?;
+        self.muted_volume = Some(volume);
+        Ok(())
+    }
+
+    /// Restore the volume stored by [`mute`](Self::mute), or
+    /// [`UNMUTE_DEFAULT_VOLUME`] if none is stored.
+    pub fn unmute(&mut self) -> Result<()> {
+        let volume = self.muted_volume.unwrap_or(UNMUTE_DEFAULT_VOLUME);
+        self.run(&["volume", &volume_level(volume)?])?;
+        self.muted_volume = None;
+        Ok(())
+    }
+
+    /// [`unmute`](Self::unmute) if muted by this handle, otherwise
+    /// [`mute`](Self::mute).
+    pub fn toggle_mute(&mut self) -> Result<()> {
+        if self.is_muted() {
+            self.unmute()
+        } else {
+            self.mute()
+        }
+    }
+
+    /// Whether this handle muted the player.
+    pub fn is_muted(&self) -> bool {
+        self.muted_volume.is_some()
+    }
+
+    /// Move the control focus to the next player of `players` and return
+    /// its name, wrapping around after the last one. The first call focuses
+    /// the first player. Returns `None` if `players` is empty.
+    ///
+    /// This only tracks the focus; combine it with
+    /// [`Playerctl::player`](crate::Playerctl::player) to control the
+    /// focused player.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::{Playerctl, PlayerctlHandle};
+    /// let mut playerctl = PlayerctlHandle::default();
+    /// let players = ["spotify", "mpv"];
+    /// // On every press of the "switch player" key:
+    /// if let Some(player) = playerctl.toggle_between(&players) {
+    ///     Playerctl::player(player).play_pause().unwrap();
+    /// }
+    /// ```
+    pub fn toggle_between<'a>(&mut self, players: &[&'a str]) -> Option<&'a str> {
+        if players.is_empty() {
+            return None;
+        }
+        let index = self.focus.map_or(0, |focus| (focus + 1) % players.len());
+        self.focus = Some(index);
+        Some(players[index])
+    }
+
+    /// List the running players without the `playerctld` proxy.
+    fn running_players(&self) -> Result<Vec<String>> {
+        let mut players = self.list_players()?;
+        players.retain(|player| !is_daemon(player));
+        Ok(players)
+    }
+
+    /// Run a playerctl command with this handle's options.
+    fn run(&self, args: &[&str]) -> Result<String> {
+        run_args_with(args, &self.config)
+    }
+
+    /// Run a playerctl command with this handle's options, returning the
+    /// untrimmed output.
+    fn run_raw(&self, args: &[&str]) -> Result<String> {
+        run_raw_with(args, &self.config)
+    }
+
+    /// Run a playerctl command with this handle's options, returning its
+    /// output lines.
+    fn run_lines(&self, args: &[&str]) -> Result<Vec<String>> {
+        run_lines_with(args, &self.config)
+    }
+}
+
+/// A [`PlayerctlHandle`] with options overridden for single calls, created
+/// by [`PlayerctlHandle::call_timeout`].
+///
+/// It dereferences to a handle with the overridden options, so all commands
+/// taking `&self` are available. Commands changing the handle's own state
+/// aren't, so e.g. muting through the override can't lose the stored
+/// volume; the state isn't copied, so [`PlayerctlHandle::is_muted`] is
+/// always `false` here.
+#[derive(Debug)]
+pub struct CallOverride {
+    /// A handle with just the overridden options, without the state.
+    handle: PlayerctlHandle,
+}
+
+impl Deref for CallOverride {
+    type Target = PlayerctlHandle;
+
+    fn deref(&self) -> &PlayerctlHandle {
+        &self.handle
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{calls, mock, mock_calls, mock_stderr, ok};
+    use std::{io, sync::Mutex};
+    use crate::{Playerctl, DEFAULT_FORMAT};
+
+    #[test]
+    fn display_uses_default_template() {
+        let playerctl = Playerctl::with_format_default("{{title}}").unwrap();
+        mock([ok("Song\n")]);
+        assert_eq!(playerctl.display(None::<&str>).unwrap(), "Song");
+        assert_eq!(calls(), [["metadata", "--format", "{{title}}"]]);
+    }
+
+    #[test]
+    fn per_call_template_takes_precedence() {
+        let playerctl = Playerctl::with_format_default("{{title}}").unwrap();
+        mock([ok("Album"), ok("Artist")]);
+        playerctl.display(Some("{{album}}")).unwrap();
+        playerctl.display(Some(crate::FormatTemplate::new().field("artist"))).unwrap();
+        assert_eq!(calls(), [["metadata", "--format", "{{album}}"], ["metadata", "--format", "{{artist}}"]]);
+    }
+
+    #[test]
+    fn lc_all_c_sets_environment() {
+        mock([ok(""), ok("")]);
+        PlayerctlHandle::default().display(None::<&str>).unwrap();
+        PlayerctlHandle::default().with_lc_all_c().display(None::<&str>).unwrap();
+        let calls = mock_calls();
+        assert!(calls[0].envs.is_empty());
+        assert_eq!(calls[1].envs, [("LC_ALL".to_owned(), Some("C".to_owned()))]);
+    }
+
+    #[test]
+    fn mute_stores_and_restores_volume() {
+        let mut playerctl = PlayerctlHandle::default();
+        mock([ok("0.8"), ok(""), ok("")]);
+        playerctl.mute().unwrap();
+        assert!(playerctl.is_muted());
+        playerctl.mute().unwrap();
+        playerctl.unmute().unwrap();
+        assert!(!playerctl.is_muted());
+        assert_eq!(calls(), [vec!["volume"], vec!["volume", "0"], vec!["volume", "0.8"]]);
+    }
+
+    #[test]
+    fn call_timeout_leaves_handle_state_alone() {
+        let mut playerctl = PlayerctlHandle::default().with_timeout(std::time::Duration::from_secs(5));
+        mock([ok("0.8"), ok(""), ok("Paused"), ok("0")]);
+        playerctl.mute().unwrap();
+        let call = playerctl.call_timeout(Some(std::time::Duration::from_millis(200)));
+        assert_eq!(call.status().unwrap(), TrackStatus::Paused);
+        assert_eq!(call.get_volume().unwrap(), 0.);
+        assert_eq!(call.config().timeout, Some(std::time::Duration::from_millis(200)));
+        assert!(playerctl.is_muted());
+        assert_eq!(playerctl.config().timeout, Some(std::time::Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn toggle_mute_flips() {
+        let mut playerctl = PlayerctlHandle::default();
+        mock([ok("0.3"), ok(""), ok("")]);
+        playerctl.toggle_mute().unwrap();
+        playerctl.toggle_mute().unwrap();
+        assert_eq!(calls()[2], ["volume", "0.3"]);
+    }
+
+    #[test]
+    fn unmute_without_stored_volume() {
+        mock([ok("")]);
+        PlayerctlHandle::default().unmute().unwrap();
+        assert_eq!(calls(), [["volume", "0.5"]]);
+    }
+
+    #[test]
+    fn rejects_empty_template() {
+        assert!(matches!(
+            Playerctl::with_format_default(" "),
+            Err(PlayerctlError::InvalidArgument(_))
+        ));
+        assert_eq!(PlayerctlHandle::default().format_default(), DEFAULT_FORMAT);
+    }
+
+    #[test]
+    fn toggle_between_wraps_around() {
+        let mut playerctl = PlayerctlHandle::default();
+        let players = ["spotify", "mpv", "firefox"];
+        let focused: Vec<_> = (0..4).map(|_| playerctl.toggle_between(&players).unwrap()).collect();
+        assert_eq!(focused, ["spotify", "mpv", "firefox", "spotify"]);
+        assert_eq!(playerctl.toggle_between(&players[..1]), Some("spotify"));
+        assert_eq!(playerctl.toggle_between(&[]), None);
+    }
+
+    #[test]
+    fn default_config_matches_static_functions() {
+        mock([ok(""), ok("")]);
+        Playerctl::play().unwrap();
+        Playerctl::with_config(PlayerctlConfig::default()).play().unwrap();
+        let calls = mock_calls();
+        assert_eq!(calls[0].program, calls[1].program);
+        assert_eq!(calls[0].args, calls[1].args);
+        assert_eq!(calls[0].envs, calls[1].envs);
+    }
+
+    #[test]
+    fn config_options_apply_to_commands() {
+        let playerctl = Playerctl::with_config(PlayerctlConfig {
+            binary: "/opt/bin/playerctl".into(),
+            env: vec![("DBUS_SESSION_BUS_ADDRESS".to_owned(), "unix:path=/bus".to_owned())],
+            lc_all_c: true,
+            format_default: "{{title}}".to_owned(),
+            ..Default::default()
+        });
+        mock([ok("Playing"), ok("Song")]);
+        assert_eq!(playerctl.status().unwrap(), TrackStatus::Playing);
+        assert_eq!(playerctl.display(None::<&str>).unwrap(), "Song");
+        let calls = mock_calls();
+        assert_eq!(calls[0].program, "/opt/bin/playerctl");
+        assert_eq!(
+            calls[0].envs,
+            [
+                ("DBUS_SESSION_BUS_ADDRESS".to_owned(), Some("unix:path=/bus".to_owned())),
+                ("LC_ALL".to_owned(), Some("C".to_owned()))
+            ]
+        );
+        assert_eq!(calls[1].args, ["metadata", "--format", "{{title}}"]);
+    }
+
+    #[test]
+    fn raw_urls_stay_encoded_by_default() {
+        let output = "mpv mpris:artUrl file:///a%20b.png\nmpv xesam:title a%20b\n";
+        mock([ok(output), ok(output)]);
+        let encoded = &PlayerctlHandle::default().metadata().unwrap()["mpv"];
+        assert_eq!(encoded.raw["mpris:artUrl"], "file:///a%20b.png");
+        assert_eq!(encoded.mpris_art_url.as_deref(), Some("file:///a b.png"));
+
+        let playerctl = Playerctl::with_config(PlayerctlConfig { decode_raw_urls: true, ..Default::default() });
+        let decoded = &playerctl.metadata().unwrap()["mpv"];
+        assert_eq!(decoded.raw["mpris:artUrl"], "file:///a b.png");
+        assert_eq!(decoded.raw["xesam:title"], "a%20b");
+        assert_eq!(decoded.mpris_art_url, encoded.mpris_art_url);
+    }
+
+    #[test]
+    fn stderr_callback_sees_warnings_on_success() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let playerctl = Playerctl::with_stderr_callback({
+            let seen = Arc::clone(&seen);
+            move |stderr| seen.lock().unwrap().push(stderr.to_owned())
+        });
+        mock([ok(""), ok("")]);
+        mock_stderr("WARNING: mpv is slow to respond\n");
+        playerctl.play().unwrap();
+        playerctl.pause().unwrap();
+        assert_eq!(*seen.lock().unwrap(), ["WARNING: mpv is slow to respond\n"]);
+    }
+
+    #[test]
+    fn working_directory_sets_child_cwd() {
+        mock([ok(""), ok("")]);
+        PlayerctlHandle::default().open("song.opus").unwrap();
+        Playerctl::with_working_directory("/music").open("song.opus").unwrap();
+        let calls = mock_calls();
+        assert_eq!(calls[0].current_dir, None);
+        assert_eq!(calls[1].current_dir.as_deref(), Some("/music"));
+        assert_eq!(calls[1].args, ["open", "song.opus"]);
+    }
+
+    #[test]
+    fn open_normalizes_paths_in_working_directory() {
+        // Tests run in the crate root, so this path is relative to it.
+        let dir = PathBuf::from(format!("target/playerctl-handle-open-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("my song.opus"), b"").unwrap();
+        let absolute = std::fs::canonicalize(dir.join("my song.opus")).unwrap();
+
+        mock([ok(""), ok(""), ok("")]);
+        let playerctl = Playerctl::with_working_directory(&dir);
+        playerctl.open("my song.opus").unwrap();
+        playerctl.open("https://example.com/a%20b.mp3").unwrap();
+        PlayerctlHandle::default().open("my song.opus").unwrap();
+        let calls = calls();
+        assert_eq!(calls[0], ["open".to_owned(), crate::file_url(absolute.to_str().unwrap())]);
+        assert_eq!(calls[1], ["open", "https://example.com/a%20b.mp3"]);
+        assert_eq!(calls[2], ["open", "my song.opus"]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn niceness_runs_the_binary_itself() {
+        mock([ok("")]);
+        Playerctl::with_spawn_niceness(10).play().unwrap();
+        let calls = mock_calls();
+        assert_eq!(calls[0].program, "playerctl");
+        assert_eq!(calls[0].args, ["play"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn niceness_keeps_commands_working() {
+        let playerctl = Playerctl::with_config(PlayerctlConfig { binary: "echo".into(), ..Default::default() })
+            .with_spawn_niceness(5);
+        assert_eq!(playerctl.run(&["hello"]).unwrap(), "hello");
+
+        // Without arguments, `nice` prints its own niceness.
+        let nice = PlayerctlConfig { binary: "nice".into(), ..Default::default() };
+        let base: i32 = Playerctl::with_config(nice.clone()).run(&[]).unwrap().parse().unwrap();
+        let niced: i32 = Playerctl::with_config(nice).with_spawn_niceness(5).run(&[]).unwrap().parse().unwrap();
+        assert_eq!(niced, (base + 5).min(19));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn niceness_keeps_missing_binaries_not_found() {
+        let playerctl =
+            Playerctl::with_config(PlayerctlConfig { binary: "playerctl-does-not-exist".into(), ..Default::default() })
+                .with_spawn_niceness(5);
+        assert!(matches!(
+            playerctl.play(),
+            Err(PlayerctlError::IoError(e)) if e.kind() == io::ErrorKind::NotFound
+        ));
+    }
+
+    #[test]
+    fn ping_reports_timeouts() {
+        let playerctl = Playerctl::with_config(PlayerctlConfig {
+            timeout: Some(std::time::Duration::from_millis(100)),
+            ..Default::default()
+        });
+        mock([Err(PlayerctlError::NoPlayer), Err(PlayerctlError::Timeout(std::time::Duration::from_millis(100)))]);
+        playerctl.ping().unwrap();
+        assert!(matches!(playerctl.ping(), Err(PlayerctlError::Timeout(_))));
+    }
+
+    #[test]
+    fn html_entities_are_decoded_on_request() {
+        let output = "firefox xesam:title Tom &amp; Jerry&#39;s &#x22;Theme&#34;\nfirefox xesam:artist A &lt;3 B\n";
+        mock([ok(output), ok(output)]);
+        let plain = &PlayerctlHandle::default().metadata().unwrap()["firefox"];
+        assert_eq!(plain.xesam_title.as_deref(), Some("Tom &amp; Jerry&#39;s &#x22;Theme&#34;"));
+
+        let playerctl = Playerctl::with_config(PlayerctlConfig { decode_html_entities: true, ..Default::default() });
+        let decoded = &playerctl.metadata().unwrap()["firefox"];
+        assert_eq!(decoded.xesam_title.as_deref(), Some("Tom & Jerry's \"Theme\""));
+        assert_eq!(decoded.xesam_artist.as_deref(), Some("A <3 B"));
+        assert_eq!(decoded.raw["xesam:title"], plain.raw["xesam:title"]);
+    }
+
+    #[test]
+    fn whitespace_is_normalized_on_request() {
+        let output = "firefox xesam:title Song\u{a0}Title  (Live)\nfirefox xesam:artist \u{a0}A \t B\u{a0}\n";
+        mock([ok(output), ok(output)]);
+        let plain = &PlayerctlHandle::default().metadata().unwrap()["firefox"];
+        assert_eq!(plain.xesam_title.as_deref(), Some("Song\u{a0}Title  (Live)"));
+
+        let playerctl = Playerctl::with_config(PlayerctlConfig { normalize_whitespace: true, ..Default::default() });
+        let normalized = &playerctl.metadata().unwrap()["firefox"];
+        assert_eq!(normalized.xesam_title.as_deref(), Some("Song Title (Live)"));
+        assert_eq!(normalized.xesam_artist.as_deref(), Some("A B"));
+        assert_eq!(normalized.raw["xesam:title"], plain.raw["xesam:title"]);
+    }
+
+    #[test]
+    fn nanosecond_lengths_are_fixed_on_request() {
+        let output = "mpv mpris:length 160680000\nvlc mpris:length 160680000000\n";
+        mock([ok(output), ok(output)]);
+        let plain = PlayerctlHandle::default().metadata().unwrap();
+        assert_eq!(plain["vlc"].mpris_length, Some(160_680_000_000));
+
+        let playerctl = Playerctl::with_config(PlayerctlConfig { fix_nanosecond_lengths: true, ..Default::default() });
+        let fixed = playerctl.metadata().unwrap();
+        assert_eq!(fixed["mpv"].mpris_length, Some(160_680_000));
+        assert_eq!(fixed["vlc"].mpris_length, Some(160_680_000));
+        assert_eq!(fixed["vlc"].raw["mpris:length"], "160680000000");
+    }
+
+    #[test]
+    fn config_applies_to_metadata_variants() {
+        let playerctl = Playerctl::with_config(PlayerctlConfig {
+            binary: "/opt/bin/playerctl".into(),
+            decode_html_entities: true,
+            ..Default::default()
+        });
+        let fields = |title: &str| {
+            let mut fields = vec!["mpv", "", "", "", title];
+            fields.resize(10, "");
+            fields.join("\u{1f}")
+        };
+        let state = format!("Playing{}E &amp; F", "\u{1f}".repeat(10));
+        mock([ok("mpv xesam:title A &amp; B\n"), ok(&fields("C &amp; D")), ok(&state)]);
+        assert_eq!(playerctl.metadata_for("mpv").unwrap().xesam_title.as_deref(), Some("A & B"));
+        assert_eq!(playerctl.metadata_fast().unwrap()["mpv"].xesam_title.as_deref(), Some("C & D"));
+        assert_eq!(playerctl.full_state("mpv").unwrap().metadata.xesam_title.as_deref(), Some("E & F"));
+        assert!(mock_calls().iter().all(|call| call.program == "/opt/bin/playerctl"));
+    }
+
+    #[test]
+    fn config_applies_to_seeking() {
+        mock([ok(""), ok(""), ok("")]);
+        let playerctl = Playerctl::with_working_directory("/music");
+        playerctl.seek(SignedDuration::Backward(Duration::from_secs(5))).unwrap();
+        playerctl.set_position(1.5).unwrap();
+        playerctl.set_position_for("mpv", 2.).unwrap();
+        let calls = mock_calls();
+        assert!(calls.iter().all(|call| call.current_dir.as_deref() == Some("/music")));
+        assert_eq!(calls[0].args, ["position", "5-"]);
+    }
+
+    #[test]
+    fn config_applies_to_whole_api() {
+        let playerctl = Playerctl::with_config(PlayerctlConfig {
+            binary: "/opt/bin/playerctl".into(),
+            decode_html_entities: true,
+            ..Default::default()
+        });
+        mock([
+            ok("0.5"),
+            ok(""),
+            ok("Track"),
+            ok(""),
+            ok("mpv\n"),
+            ok("mpv;-;1000000\n"),
+            ok("Song"),
+            ok("mpv\n"),
+            ok("mpv xesam:title A &amp; B\n"),
+        ]);
+        assert_eq!(playerctl.get_volume_for("mpv").unwrap(), 0.5);
+        playerctl.set_volume(0.2).unwrap();
+        assert_eq!(playerctl.get_loop_for("mpv").unwrap(), crate::LoopStatus::Track);
+        playerctl.set_shuffle_for("mpv", true).unwrap();
+        assert_eq!(playerctl.list_players().unwrap(), ["mpv"]);
+        assert_eq!(playerctl.get_position().unwrap()["mpv"], 1_000_000);
+        assert_eq!(playerctl.format("{{title}}").unwrap(), "Song");
+        let poll = Duration::from_millis(1);
+        assert_eq!(playerctl.wait_for_player(poll, poll).unwrap().as_deref(), Some("mpv"));
+        let metadata = playerctl.metadata_for_many(&["mpv"], 1).remove("mpv").unwrap().unwrap();
+        assert_eq!(metadata.xesam_title.as_deref(), Some("A & B"));
+        let calls = mock_calls();
+        assert_eq!(calls.len(), 9);
+        assert!(calls.iter().all(|call| call.program == "/opt/bin/playerctl"));
+    }
+
+    #[test]
+    fn command_hooks_fire_around_commands() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let playerctl = Playerctl::with_before_command_hook({
+            let events = Arc::clone(&events);
+            move |args| events.lock().unwrap().push(format!("before {args:?}"))
+        })
+        .with_after_command_hook({
+            let events = Arc::clone(&events);
+            move |args, result, _elapsed| events.lock().unwrap().push(format!("after {args:?} {}", result.is_ok()))
+        });
+        mock([ok(""), Err(PlayerctlError::NoPlayer)]);
+        playerctl.play().unwrap();
+        playerctl.next().unwrap_err();
+        assert_eq!(
+            *events.lock().unwrap(),
+            [
+                r#"before ["play"]"#,
+                r#"after ["play"] true"#,
+                r#"before ["next"]"#,
+                r#"after ["next"] false"#
+            ]
+        );
+    }
+
+    #[test]
+    fn retries_failed_commands() {
+        let playerctl = Playerctl::with_config(PlayerctlConfig { retries: 2, ..Default::default() });
+        let failure = || Err(PlayerctlError::CommandError("bus busy".to_owned()));
+        mock([failure(), failure(), ok("")]);
+        playerctl.next().unwrap();
+        assert_eq!(calls().len(), 3);
+
+        mock([failure(), failure(), failure()]);
+        assert!(matches!(playerctl.next(), Err(PlayerctlError::CommandError(_))));
+        assert_eq!(calls().len(), 3);
+
+        mock([Err(PlayerctlError::NoPlayer)]);
+        assert!(matches!(playerctl.next(), Err(PlayerctlError::NoPlayer)));
+        assert_eq!(calls().len(), 1);
+
+        mock([Err(PlayerctlError::Unavailable("no seeking".to_owned())), ok("")]);
+        assert!(matches!(playerctl.set_position(1.), Err(PlayerctlError::Unavailable(_))));
+        assert_eq!(calls().len(), 1);
+
+        let not_found = || Err(io::Error::from(io::ErrorKind::NotFound).into());
+        mock([not_found(), ok("")]);
+        assert!(matches!(playerctl.next(), Err(PlayerctlError::IoError(_))));
+        assert_eq!(calls().len(), 1);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn call_timeout_overrides_handle_timeout() {
+        let playerctl = Playerctl::with_config(PlayerctlConfig { binary: "sleep".into(), ..Default::default() })
+            .with_timeout(std::time::Duration::from_secs(30));
+        let short = std::time::Duration::from_millis(100);
+        let start = std::time::Instant::now();
+        let result = playerctl.call_timeout(Some(short)).handle.run(&["5"]);
+        assert!(matches!(result, Err(PlayerctlError::Timeout(t)) if t == short));
+        assert!(start.elapsed() < std::time::Duration::from_secs(3));
+        assert_eq!(playerctl.config().timeout, Some(std::time::Duration::from_secs(30)));
+        assert_eq!(playerctl.call_timeout(None).config().timeout, None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn timeout_kills_slow_commands() {
+        let playerctl = Playerctl::with_config(PlayerctlConfig {
+            binary: "sleep".into(),
+            timeout: Some(std::time::Duration::from_millis(100)),
+            ..Default::default()
+        });
+        let start = std::time::Instant::now();
+        assert!(matches!(playerctl.run(&["5"]), Err(PlayerctlError::Timeout(_))));
+        assert!(start.elapsed() < std::time::Duration::from_secs(3));
+        assert_eq!(playerctl.run(&["0"]).unwrap(), "");
+    }
+}
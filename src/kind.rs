@@ -0,0 +1,110 @@
+//! Classifying players by their name.
+
+use crate::Playerctl;
+
+/// What kind of application a player is, see [`Playerctl::player_kind`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PlayerKind {
+    /// A web browser, e.g. Firefox or Chromium.
+    Browser,
+    /// A music player, e.g. Spotify or mpd.
+    MusicPlayer,
+    /// A video player, e.g. mpv or VLC.
+    VideoPlayer,
+    /// A player not in the mapping.
+    Unknown,
+}
+
+/// Built-in mapping of player base names to their kind.
+const KNOWN_PLAYERS: &[(&str, PlayerKind)] = &[
+    ("brave", PlayerKind::Browser),
+    ("chrome", PlayerKind::Browser),
+    ("chromium", PlayerKind::Browser),
+    ("epiphany", PlayerKind::Browser),
+    ("firefox", PlayerKind::Browser),
+    ("google-chrome", PlayerKind::Browser),
+    ("microsoft-edge", PlayerKind::Browser),
+    ("opera", PlayerKind::Browser),
+    ("plasma-browser-integration", PlayerKind::Browser),
+    ("vivaldi", PlayerKind::Browser),
+    ("amarok", PlayerKind::MusicPlayer),
+    ("audacious", PlayerKind::MusicPlayer),
+    ("clementine", PlayerKind::MusicPlayer),
+    ("cmus", PlayerKind::MusicPlayer),
+    ("deadbeef", PlayerKind::MusicPlayer),
+    ("elisa", PlayerKind::MusicPlayer),
+    ("lollypop", PlayerKind::MusicPlayer),
+    ("mpd", PlayerKind::MusicPlayer),
+    ("ncspot", PlayerKind::MusicPlayer),
+    ("quodlibet", PlayerKind::MusicPlayer),
+    ("rhythmbox", PlayerKind::MusicPlayer),
+    ("spotify", PlayerKind::MusicPlayer),
+    ("spotifyd", PlayerKind::MusicPlayer),
+    ("strawberry", PlayerKind::MusicPlayer),
+    ("celluloid", PlayerKind::VideoPlayer),
+    ("haruna", PlayerKind::VideoPlayer),
+    ("kodi", PlayerKind::VideoPlayer),
+    ("mpv", PlayerKind::VideoPlayer),
+    ("smplayer", PlayerKind::VideoPlayer),
+    ("totem", PlayerKind::VideoPlayer),
+    ("vlc", PlayerKind::VideoPlayer),
+];
+
+impl Playerctl {
+    /// Classify the player `name`, e.g. to pick an icon, using a built-in
+    /// mapping of common players.
+    ///
+    /// Only the base name counts, so `firefox.instance_1_23` is a browser.
+    /// Names are compared case-insensitively.
+    ///
+    /// ```
+    /// # use playerctl_rust_wrapper::{PlayerKind, Playerctl};
+    /// assert_eq!(Playerctl::player_kind("chromium.instance1234"), PlayerKind::Browser);
+    /// assert_eq!(Playerctl::player_kind("my-player"), PlayerKind::Unknown);
+    /// ```
+    pub fn player_kind(name: &str) -> PlayerKind {
+        Self::player_kind_with(name, &[])
+    }
+
+    /// Classify the player `name` like [`Playerctl::player_kind`], looking it
+    /// up in `extra` before the built-in mapping, so entries there add to or
+    /// override it.
+    ///
+    /// ```
+    /// # use playerctl_rust_wrapper::{PlayerKind, Playerctl};
+    /// let extra = [("my-player", PlayerKind::MusicPlayer), ("mpv", PlayerKind::MusicPlayer)];
+    /// assert_eq!(Playerctl::player_kind_with("my-player", &extra), PlayerKind::MusicPlayer);
+    /// assert_eq!(Playerctl::player_kind_with("mpv", &extra), PlayerKind::MusicPlayer);
+    /// ```
+    pub fn player_kind_with(name: &str, extra: &[(&str, PlayerKind)]) -> PlayerKind {
+        let base = name.split('.').next().unwrap_or_default();
+        extra
+            .iter()
+            .chain(KNOWN_PLAYERS)
+            .find(|(known, _)| known.eq_ignore_ascii_case(base))
+            .map_or(PlayerKind::Unknown, |(_, kind)| *kind)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_known_players() {
+        assert_eq!(Playerctl::player_kind("firefox"), PlayerKind::Browser);
+        assert_eq!(Playerctl::player_kind("firefox.instance_1_23"), PlayerKind::Browser);
+        assert_eq!(Playerctl::player_kind("Spotify"), PlayerKind::MusicPlayer);
+        assert_eq!(Playerctl::player_kind("mpd"), PlayerKind::MusicPlayer);
+        assert_eq!(Playerctl::player_kind("vlc"), PlayerKind::VideoPlayer);
+    }
+
+    #[test]
+    fn unknown_players_fall_back() {
+        assert_eq!(Playerctl::player_kind("kdeconnect"), PlayerKind::Unknown);
+        assert_eq!(Playerctl::player_kind(""), PlayerKind::Unknown);
+        let extra = [("kdeconnect", PlayerKind::MusicPlayer)];
+        assert_eq!(Playerctl::player_kind_with("kdeconnect.phone", &extra), PlayerKind::MusicPlayer);
+        assert_eq!(Playerctl::player_kind_with("vlc", &extra), PlayerKind::VideoPlayer);
+    }
+}
@@ -6,7 +6,46 @@
 //! See the [playerctl](https://github.com/altdesktop/playerctl) project for
 //! more information.
 
-use std::{collections::HashMap, num::ParseIntError, process::Command, string::FromUtf8Error};
+pub mod prelude;
+
+mod cache;
+mod config;
+mod controls;
+mod follow;
+mod handle;
+mod kind;
+mod player;
+mod probe;
+mod seek;
+mod template;
+
+pub use cache::CachedPlayerctl;
+pub use config::{AfterCommandHook, BeforeCommandHook, PlayerctlConfig, StderrCallback, DEFAULT_FORMAT};
+pub use controls::PlayerControls;
+pub use follow::{DebouncedFollow, Follow, FollowDrain, FormattedFollow, PlayerEvent};
+pub use handle::{CallOverride, PlayerctlHandle, UNMUTE_DEFAULT_VOLUME};
+pub use kind::PlayerKind;
+pub use player::PlayerInstance;
+pub use probe::Probe;
+pub use seek::SignedDuration;
+pub use template::{FormatTemplate, TemplatePart};
+
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    io::{self, Read},
+    num::ParseIntError,
+    path::{Path, PathBuf},
+    process::{Command, Output, Stdio},
+    str::FromStr,
+    string::FromUtf8Error,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
 use thiserror::Error;
 
 /// Playerctl errors.
@@ -16,10 +55,30 @@ pub enum PlayerctlError {
     IoError(#[from] std::io::Error),
     #[error("Command error: {0}")]
     CommandError(String),
+    #[error("No player is running")]
+    NoPlayer,
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+    #[error("Unavailable: {0}")]
+    Unavailable(String),
     #[error("Failed to parse track length: {0}")]
     ParseLengthError(#[from] ParseIntError),
     #[error("Failed to parse metadata URL: {0}")]
     ParseUrlError(#[from] FromUtf8Error),
+    #[cfg(feature = "json")]
+    #[error("JSON error: {0}")]
+    JsonError(#[from] serde_json::Error),
+    #[error("Opened only {opened} of {total} URIs: {source}")]
+    OpenFailed {
+        opened: usize,
+        total: usize,
+        #[source]
+        source: Box<PlayerctlError>,
+    },
+    #[error("Failed to parse version: {0}")]
+    ParseVersionError(String),
+    #[error("Command timed out after {0:?}")]
+    Timeout(Duration),
     #[error("Other error: {0}")]
     Other(String),
 }
@@ -27,6 +86,7 @@ pub type Result<T> = std::result::Result<T, PlayerctlError>;
 
 /// The current track status.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TrackStatus {
     /// Track is playing.
     Playing,
@@ -36,234 +96,4391 @@ pub enum TrackStatus {
     Stopped,
 }
 
+impl TrackStatus {
+    /// Whether the track is playing.
+    pub fn is_playing(self) -> bool {
+        self == TrackStatus::Playing
+    }
+
+    /// Whether the track is paused.
+    pub fn is_paused(self) -> bool {
+        self == TrackStatus::Paused
+    }
+
+    /// Whether the track is stopped.
+    pub fn is_stopped(self) -> bool {
+        self == TrackStatus::Stopped
+    }
+}
+
+/// The loop status of a player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LoopStatus {
+    /// Playback stops at the end of the playlist.
+    None,
+    /// The current track is repeated.
+    Track,
+    /// The playlist is repeated.
+    Playlist,
+}
+
+impl LoopStatus {
+    /// The status as used by playerctl (`None`, `Track` or `Playlist`).
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LoopStatus::None => "None",
+            LoopStatus::Track => "Track",
+            LoopStatus::Playlist => "Playlist",
+        }
+    }
+}
+
+impl FromStr for LoopStatus {
+    type Err = PlayerctlError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim() {
+            "None" => Ok(LoopStatus::None),
+            "Track" => Ok(LoopStatus::Track),
+            "Playlist" => Ok(LoopStatus::Playlist),
+            other => Err(PlayerctlError::Other(format!("unknown loop status {other:?}"))),
+        }
+    }
+}
+
+impl fmt::Display for LoopStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Where the album/thumbnail art of a track is, as returned by
+/// [`PlayerMetadata::art`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArtSource {
+    /// A local file, from a `file://` URL.
+    File(PathBuf),
+    /// Any other URL, e.g. `https://`, to be fetched by the caller.
+    Remote(String),
+}
+
+/// A playerctl version, as reported by `playerctl --version`.
+///
+/// Versions compare by major, minor and patch number.
+///
+/// ```
+/// # use playerctl_rust_wrapper::Version;
+/// let version: Version = "v2.4.1".parse().unwrap();
+/// assert_eq!(version, Version { major: 2, minor: 4, patch: 1 });
+/// assert!(version >= Version { major: 2, minor: 1, patch: 0 });
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl FromStr for Version {
+    type Err = PlayerctlError;
+
+    /// Parse `major.minor[.patch]`, with an optional `v` prefix. Anything
+    /// after a `-` or `+` (e.g. a git describe suffix) is ignored.
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || PlayerctlError::ParseVersionError(s.to_owned());
+        let s = s.trim();
+        let s = s.strip_prefix('v').unwrap_or(s);
+        let s = s.split(['-', '+']).next().unwrap_or_default();
+        let mut parts = s.split('.').map(|part| part.parse::<u32>().map_err(|_| invalid()));
+        let major = parts.next().ok_or_else(invalid)??;
+        let minor = parts.next().ok_or_else(invalid)??;
+        let patch = parts.next().transpose()?.unwrap_or(0);
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+        Ok(Self { major, minor, patch })
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
 /// Player metadata struct.
 /// 
 /// There are many more xesam properties.
 /// 
 /// Reference: [MPRIS v2 metadata guidelines](https://freedesktop.org/wiki/Specifications/mpris-spec/metadata/)
-#[derive(Default, Debug, PartialEq, Eq)]
+///
+/// With the `serde` feature, this struct can be (de)serialized. Fields that
+/// are `None` are omitted when serializing.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct PlayerMetadata {
     /// Example values: `'/org/mpris/MediaPlayer2/firefox'` (Firefox playing a Youtube video), `'/63'` (mpv, playlist item 63)
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub mpris_trackid: Option<String>,
-    /// Album/thumbnail art.
+    /// Album/thumbnail art URL, percent-decoded. See
+    /// [`PlayerMetadata::art_url_raw`] for the URL as reported by the player.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub mpris_art_url: Option<String>,
     /// Length in microseconds.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub mpris_length: Option<u64>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub xesam_title: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub xesam_album: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub xesam_artist: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub xesam_album_artist: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub xesam_url: Option<String>,
     /// Example value: 2018-06-28T00:00:00
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub xesam_content_created: Option<String>,
-    /// Raw metadata values.
-    pub raw: HashMap<String, String>
+    /// Raw metadata values by key, as printed by playerctl. URL values stay
+    /// percent-encoded, unlike the typed fields, unless
+    /// [`PlayerctlConfig::decode_raw_urls`] is set.
+    ///
+    /// If a player reports a key more than once, the last value wins here
+    /// and in the typed fields; all values are kept in
+    /// [`raw_multi`](Self::raw_multi).
+    pub raw: HashMap<String, String>,
+    /// All values, in order, of keys the player reported more than once.
+    /// Keys reported once are only in [`raw`](Self::raw), so this is empty
+    /// for well-behaved players.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "HashMap::is_empty"))]
+    pub raw_multi: HashMap<String, Vec<String>>,
 }
 
-/// # Playerctl
-///
-/// Playerctl wrapper struct allowing to send commands and control
-/// the player.
-///
-/// # Examples
-///
-/// ## Stop the current player
-///
-/// ```
-/// Playerctl::stop().unwrap();
-/// ```
-///
-/// ## Advance and rewind 10 seconds
-///
-/// ```
-/// Playerctl::position(10.).unwrap();
-/// Playerctl::position(-10.).unwrap();
-/// ```
-///
-/// ## Get metadata
-///
-/// ```
-/// let metadata = Playerctl::metadata().unwrap();
-///
-/// println!("Track title: {}", metadata.title);
-/// ```
-pub struct Playerctl;
+impl PlayerMetadata {
+    /// Parse the raw value of `key` as an unsigned integer.
+    ///
+    /// Returns `None` if the key is missing or not a valid number.
+    ///
+    /// ```
+    /// # use playerctl_rust_wrapper::PlayerMetadata;
+    /// let mut metadata = PlayerMetadata::default();
+    /// metadata.raw.insert("xesam:trackNumber".to_owned(), "7".to_owned());
+    /// assert_eq!(metadata.get_u64("xesam:trackNumber"), Some(7));
+    /// ```
+    pub fn get_u64(&self, key: &str) -> Option<u64> {
+        self.raw.get(key)?.trim().parse().ok()
+    }
 
-impl Playerctl {
-    /// Command the player to play.
+    /// Parse the raw value of `key` as a floating point number.
+    ///
+    /// Returns `None` if the key is missing or not a valid number.
+    pub fn get_f64(&self, key: &str) -> Option<f64> {
+        self.raw.get(key)?.trim().parse().ok()
+    }
+
+    /// Parse the raw value of `key` as a boolean, accepting `true`/`false`,
+    /// `1`/`0` and `on`/`off` in any case.
+    ///
+    /// Returns `None` if the key is missing or not a recognized boolean.
     ///
     /// ```
-    /// Playerctl::play().unwrap();
+    /// # use playerctl_rust_wrapper::PlayerMetadata;
+    /// let mut metadata = PlayerMetadata::default();
+    /// metadata.raw.insert("xesam:explicit".to_owned(), "True".to_owned());
+    /// assert_eq!(metadata.get_bool("xesam:explicit"), Some(true));
     /// ```
-    pub fn play() -> Result<()> {
-        run_command("play")?;
-        Ok(())
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        let val = self.raw.get(key)?.trim();
+        if ["true", "1", "on"].iter().any(|s| val.eq_ignore_ascii_case(s)) {
+            Some(true)
+        } else if ["false", "0", "off"].iter().any(|s| val.eq_ignore_ascii_case(s)) {
+            Some(false)
+        } else {
+            None
+        }
     }
 
-    /// Command the player to pause.
+    /// The track length, or `None` if the player doesn't report
+    /// `mpris:length`, e.g. for live streams. A reported length of zero is
+    /// kept as zero, so UIs can tell "unknown" from "empty".
     ///
     /// ```
-    /// Playerctl::pause().unwrap();
+    /// # use std::time::Duration;
+    /// # use playerctl_rust_wrapper::PlayerMetadata;
+    /// let metadata = PlayerMetadata { mpris_length: Some(1_500_000), ..Default::default() };
+    /// assert_eq!(metadata.length(), Some(Duration::from_millis(1500)));
+    /// assert_eq!(PlayerMetadata::default().length(), None);
     /// ```
-    pub fn pause() -> Result<()> {
-        run_command("pause")?;
-        Ok(())
+    pub fn length(&self) -> Option<Duration> {
+        self.mpris_length.map(Duration::from_micros)
     }
 
-    /// Command the player to toggle between play/pause.
+    /// Whether the player reports the track length, see
+    /// [`PlayerMetadata::length`].
+    pub fn has_length(&self) -> bool {
+        self.mpris_length.is_some()
+    }
+
+    /// Audio bitrate, from the first of the known bitrate keys
+    /// (`xesam:audioBitrate`, `xesam:bitrate`) that parses as a number.
+    pub fn bitrate(&self) -> Option<u32> {
+        self.get_first_u32(&["xesam:audioBitrate", "xesam:bitrate"])
+    }
+
+    /// Audio sample rate, from the first of the known sample rate keys
+    /// (`xesam:audioSampleRate`, `xesam:sampleRate`) that parses as a number.
+    pub fn sample_rate(&self) -> Option<u32> {
+        self.get_first_u32(&["xesam:audioSampleRate", "xesam:sampleRate"])
+    }
+
+    /// The track artists. playerctl prints the `xesam:artist` list joined
+    /// with `", "`, so this splits on that separator; artist names that
+    /// contain `", "` themselves are split as well.
     ///
     /// ```
-    /// Playerctl::play_pause().unwrap();
+    /// # use playerctl_rust_wrapper::PlayerMetadata;
+    /// let metadata = PlayerMetadata {
+    ///     xesam_artist: Some("Daft Punk, Pharrell Williams".to_owned()),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(metadata.artists(), ["Daft Punk", "Pharrell Williams"]);
     /// ```
-    pub fn play_pause() -> Result<()> {
-        run_command("play-pause")?;
-        Ok(())
+    pub fn artists(&self) -> Vec<&str> {
+        self.xesam_artist
+            .as_deref()
+            .map(|artists| artists.split(", ").map(str::trim).filter(|artist| !artist.is_empty()).collect())
+            .unwrap_or_default()
     }
 
-    /// Command the player to stop.
+    /// The track comments (`xesam:comment`), e.g. show notes of podcasts.
+    /// Returns `None` if the player reports no comment.
+    ///
+    /// playerctl prints a list of comments joined with `", "` on one line,
+    /// which can't be told apart from a single comment containing `", "`,
+    /// so such a line is returned as one comment. Only comments reported
+    /// on lines of their own (see [`raw_multi`](Self::raw_multi)) are
+    /// returned separately.
     ///
     /// ```
-    /// Playerctl::stop().unwrap();
+    /// # use playerctl_rust_wrapper::PlayerMetadata;
+    /// let mut metadata = PlayerMetadata::default();
+    /// metadata.raw.insert("xesam:comment".to_owned(), "Episode 12".to_owned());
+    /// assert_eq!(metadata.comments(), Some(vec!["Episode 12".to_owned()]));
     /// ```
-    pub fn stop() -> Result<()> {
-        run_command("stop")?;
-        Ok(())
+    pub fn comments(&self) -> Option<Vec<String>> {
+        let comments = match self.raw_multi.get("xesam:comment") {
+            Some(comments) => comments.clone(),
+            None => vec![self.raw.get("xesam:comment")?.clone()],
+        };
+        let comments: Vec<_> = comments.into_iter().filter(|comment| !comment.trim().is_empty()).collect();
+        (!comments.is_empty()).then_some(comments)
     }
 
-    /// Command the player to skip to the next track.
+    /// The track artists for display, joined as `"A, B & C"`. Returns `None`
+    /// if there are no artists.
     ///
     /// ```
-    /// Playerctl::next().unwrap();
+    /// # use playerctl_rust_wrapper::PlayerMetadata;
+    /// let metadata = PlayerMetadata {
+    ///     xesam_artist: Some("A, B, C".to_owned()),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(metadata.artist_display().as_deref(), Some("A, B & C"));
     /// ```
-    pub fn next() -> Result<()> {
-        run_command("next")?;
-        Ok(())
+    pub fn artist_display(&self) -> Option<String> {
+        match self.artists().as_slice() {
+            [] => None,
+            [artist] => Some((*artist).to_owned()),
+            [init @ .., last] => Some(format!("{} & {last}", init.join(", "))),
+        }
     }
 
-    /// Command the player to skip to the previous track.
+    /// "The" artist to display: the first track artist (see
+    /// [`artists`](Self::artists)), falling back to the first album artist.
+    /// Returns `None` if neither is reported.
     ///
     /// ```
-    /// Playerctl::previous().unwrap();
+    /// # use playerctl_rust_wrapper::PlayerMetadata;
+    /// let metadata = PlayerMetadata {
+    ///     xesam_album_artist: Some("Various Artists".to_owned()),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(metadata.primary_artist().as_deref(), Some("Various Artists"));
     /// ```
-    pub fn previous() -> Result<()> {
-        run_command("previous")?;
-        Ok(())
+    pub fn primary_artist(&self) -> Option<String> {
+        let album_artist = || {
+            self.xesam_album_artist
+                .as_deref()?
+                .split(", ")
+                .map(str::trim)
+                .find(|artist| !artist.is_empty())
+        };
+        self.artists().first().copied().or_else(album_artist).map(str::to_owned)
     }
 
-    /// Command the player to seek forward/backward OFFSET in seconds.
+    /// The automatic rating (`xesam:autoRating`), e.g. based on play
+    /// count. Returns `None` if it is missing or not a number.
+    ///
+    /// The spec range is 0.0 to 1.0, but some players exceed it, so values
+    /// are clamped into that range rather than rejected.
     ///
     /// ```
-    /// Playerctl::position(10.).unwrap();
+    /// # use playerctl_rust_wrapper::PlayerMetadata;
+    /// let mut metadata = PlayerMetadata::default();
+    /// metadata.raw.insert("xesam:autoRating".to_owned(), "0.8".to_owned());
+    /// assert_eq!(metadata.auto_rating(), Some(0.8));
     /// ```
-    pub fn position(secs: f32) -> Result<()> {
-        if secs < 0. {
-            run_command(&format!("position {}-", -secs))?;
-        } else {
-            run_command(&format!("position {}+", secs))?;
+    pub fn auto_rating(&self) -> Option<f64> {
+        self.get_f64("xesam:autoRating")
+            .filter(|rating| !rating.is_nan())
+            .map(|rating| rating.clamp(0., 1.))
+    }
+
+    /// Merge `newer` metadata of the same player into this one. Fields and
+    /// raw values present in `newer` win, missing ones keep their current
+    /// value.
+    ///
+    /// ```
+    /// # use playerctl_rust_wrapper::PlayerMetadata;
+    /// let mut metadata = PlayerMetadata { xesam_title: Some("Song".to_owned()), ..Default::default() };
+    /// metadata.merge(PlayerMetadata { mpris_length: Some(1000), ..Default::default() });
+    /// assert_eq!(metadata.xesam_title.as_deref(), Some("Song"));
+    /// assert_eq!(metadata.mpris_length, Some(1000));
+    /// ```
+    pub fn merge(&mut self, newer: PlayerMetadata) {
+        fn keep_newer<T>(field: &mut Option<T>, newer: Option<T>) {
+            if newer.is_some() {
+                *field = newer;
+            }
         }
+        keep_newer(&mut self.mpris_trackid, newer.mpris_trackid);
+        keep_newer(&mut self.mpris_art_url, newer.mpris_art_url);
+        keep_newer(&mut self.mpris_length, newer.mpris_length);
+        keep_newer(&mut self.xesam_title, newer.xesam_title);
+        keep_newer(&mut self.xesam_album, newer.xesam_album);
+        keep_newer(&mut self.xesam_artist, newer.xesam_artist);
+        keep_newer(&mut self.xesam_album_artist, newer.xesam_album_artist);
+        keep_newer(&mut self.xesam_url, newer.xesam_url);
+        keep_newer(&mut self.xesam_content_created, newer.xesam_content_created);
+        self.raw.extend(newer.raw);
+        self.raw_multi.extend(newer.raw_multi);
+    }
 
-        Ok(())
+    fn get_first_u32(&self, keys: &[&str]) -> Option<u32> {
+        keys.iter()
+            .find_map(|key| self.raw.get(*key)?.trim().parse().ok())
     }
 
-    /// Get current player positions, in microseconds.
-    pub fn get_position() -> Result<HashMap<String, u64>> {
-        let output = run_command("status -a -f {{playerName}};-;{{position}}")?;
-        let mut m = HashMap::new();
-        for line in output.lines() {
-            let Some((name, pos)) = line.split_once(";-;") else {
-                continue;
-            };
-            m.insert(name.to_owned(), pos.parse()?);
-        }
-        Ok(m)
+    /// The typed fields by field name, rendered as strings.
+    fn typed_fields(&self) -> [(&'static str, Option<String>); 9] {
+        [
+            ("mpris_trackid", self.mpris_trackid.clone()),
+            ("mpris_art_url", self.mpris_art_url.clone()),
+            ("mpris_length", self.mpris_length.map(|length| length.to_string())),
+            ("xesam_title", self.xesam_title.clone()),
+            ("xesam_album", self.xesam_album.clone()),
+            ("xesam_artist", self.xesam_artist.clone()),
+            ("xesam_album_artist", self.xesam_album_artist.clone()),
+            ("xesam_url", self.xesam_url.clone()),
+            ("xesam_content_created", self.xesam_content_created.clone()),
+        ]
     }
 
-    /// Set the volume to LEVEL from 0.0 to 1.0.
+    /// The raw value of `key`, percent-decoded, e.g. for URL keys beyond
+    /// the ones decoded into the typed fields.
+    ///
+    /// Returns `None` if the key is missing or doesn't decode to valid
+    /// UTF-8. Values without escapes are returned unchanged.
     ///
     /// ```
-    /// Playerctl::volume(10.).unwrap();
+    /// # use playerctl_rust_wrapper::PlayerMetadata;
+    /// let mut metadata = PlayerMetadata::default();
+    /// metadata.raw.insert("custom:lyricsUrl".to_owned(), "file:///a%20b.lrc".to_owned());
+    /// assert_eq!(metadata.decoded("custom:lyricsUrl").as_deref(), Some("file:///a b.lrc"));
     /// ```
-    pub fn volume(percent: f32) -> Result<()> {
-        if percent < 0. {
-            run_command(&format!("volume {}-", -percent))?;
-        } else {
-            run_command(&format!("volume {}+", percent))?;
-        }
+    pub fn decoded(&self, key: &str) -> Option<String> {
+        decode_url(self.raw.get(key)?)
+    }
 
-        Ok(())
+    /// The track id as a plain DBus object path, for comparing track ids
+    /// across playerctl versions.
+    ///
+    /// Depending on the version, playerctl prints the track id quoted
+    /// (`'/63'`) or with a GVariant type prefix (`objectpath '/63'`,
+    /// `@o '/63'`); both are stripped. Returns `None` if there is no track
+    /// id.
+    ///
+    /// ```
+    /// # use playerctl_rust_wrapper::PlayerMetadata;
+    /// let metadata = PlayerMetadata {
+    ///     mpris_trackid: Some("'/org/mpris/MediaPlayer2/firefox'".to_owned()),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(metadata.track_id_normalized().as_deref(), Some("/org/mpris/MediaPlayer2/firefox"));
+    /// ```
+    pub fn track_id_normalized(&self) -> Option<String> {
+        let id = self.mpris_trackid.as_deref()?.trim();
+        let id = ["objectpath ", "@o "]
+            .iter()
+            .find_map(|prefix| id.strip_prefix(prefix))
+            .unwrap_or(id)
+            .trim();
+        let id = ['\'', '"']
+            .iter()
+            .find_map(|&quote| id.strip_prefix(quote)?.strip_suffix(quote))
+            .unwrap_or(id);
+        (!id.is_empty()).then(|| id.to_owned())
     }
 
-    /// Get the play status of the player.
+    /// The album/thumbnail art URL exactly as reported by the player, i.e.
+    /// still percent-encoded, unlike [`mpris_art_url`](Self::mpris_art_url).
+    ///
+    /// Use this when passing the URL on to another tool that decodes it
+    /// itself, to avoid decoding twice.
     ///
     /// ```
-    /// Playerctl::status(10.).unwrap();
+    /// # use playerctl_rust_wrapper::PlayerMetadata;
+    /// let mut metadata = PlayerMetadata::default();
+    /// metadata.raw.insert("mpris:artUrl".to_owned(), "file:///a%20b.png".to_owned());
+    /// assert_eq!(metadata.art_url_raw(), Some("file:///a%20b.png"));
     /// ```
-    pub fn status() -> Result<TrackStatus> {
-        let status = run_command("status")?;
+    pub fn art_url_raw(&self) -> Option<&str> {
+        self.raw.get("mpris:artUrl").map(String::as_str)
+    }
+
+    /// Read the album/thumbnail art into memory, if it is a local
+    /// `file://` URL.
+    ///
+    /// Returns `None` for remote art (e.g. `https://`), which is left to the
+    /// caller. This performs blocking IO.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// let metadata = Playerctl::metadata().unwrap();
+    /// if let Some(art) = metadata["mpv"].art_bytes() {
+    ///     println!("{} bytes of art", art.unwrap().len());
+    /// }
+    /// ```
+    pub fn art_bytes(&self) -> Option<io::Result<Vec<u8>>> {
+        Some(fs::read(self.art_path()?))
+    }
 
-        match status.as_str().trim() {
-            "Playing" => Ok(TrackStatus::Playing),
-            "Paused" => Ok(TrackStatus::Paused),
-            _ => Ok(TrackStatus::Stopped),
+    /// Local path of the album/thumbnail art, if it is a `file://` URL.
+    ///
+    /// ```
+    /// # use playerctl_rust_wrapper::PlayerMetadata;
+    /// let metadata = PlayerMetadata {
+    ///     mpris_art_url: Some("file:///music/cover.jpg".to_owned()),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(metadata.art_path(), Some("/music/cover.jpg".into()));
+    /// ```
+    pub fn art_path(&self) -> Option<PathBuf> {
+        file_url_path(self.mpris_art_url.as_deref()?)
+    }
+
+    /// The album/thumbnail art, as a local file or a remote URL.
+    ///
+    /// Returns `None` if there is no art, or its URL has no scheme.
+    ///
+    /// ```
+    /// # use playerctl_rust_wrapper::{ArtSource, PlayerMetadata};
+    /// let metadata = PlayerMetadata {
+    ///     mpris_art_url: Some("https://example.com/cover.jpg".to_owned()),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(metadata.art(), Some(ArtSource::Remote("https://example.com/cover.jpg".to_owned())));
+    /// ```
+    pub fn art(&self) -> Option<ArtSource> {
+        let url = self.mpris_art_url.as_deref()?;
+        match file_url_path(url) {
+            Some(path) => Some(ArtSource::File(path)),
+            None => has_scheme(url).then(|| ArtSource::Remote(url.to_owned())),
         }
     }
 
-    /// Get metadata information for all active players.
+    /// Background art URL (`mpris:backgroundUrl`), percent-decoded like
+    /// [`mpris_art_url`](Self::mpris_art_url). Only some players provide it.
     ///
     /// ```
-    /// let metadata = Playerctl::metadata().unwrap();
+    /// # use playerctl_rust_wrapper::PlayerMetadata;
+    /// let mut metadata = PlayerMetadata::default();
+    /// metadata.raw.insert("mpris:backgroundUrl".to_owned(), "file:///a%20b.jpg".to_owned());
+    /// assert_eq!(metadata.background_url().as_deref(), Some("file:///a b.jpg"));
+    /// ```
+    pub fn background_url(&self) -> Option<String> {
+        decode_url(self.raw.get("mpris:backgroundUrl")?)
+    }
+
+    /// The video ID of a YouTube `xesam:url`, as reported by browsers.
+    ///
+    /// Recognized are `youtube.com/watch?v=<id>` (on any `youtube.com`
+    /// subdomain, such as `music.youtube.com`) and `youtu.be/<id>`. Returns
+    /// `None` for other URLs.
     ///
-    /// println!("Title: {}", metadata.values().first().unwrap().xesam_title.unwrap());
     /// ```
-    pub fn metadata() -> Result<HashMap<String, PlayerMetadata>> {
-        let mut data: HashMap<_, PlayerMetadata> = HashMap::new();
+    /// # use playerctl_rust_wrapper::PlayerMetadata;
+    /// let metadata = PlayerMetadata {
+    ///     xesam_url: Some("https://www.youtube.com/watch?v=I9nK9FggzIg&t=10".to_owned()),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(metadata.stream_id().as_deref(), Some("I9nK9FggzIg"));
+    /// ```
+    pub fn stream_id(&self) -> Option<String> {
+        let url = self.xesam_url.as_deref()?;
+        let rest = url.strip_prefix("https://").or_else(|| url.strip_prefix("http://"))?;
+        let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+        let id = if host == "youtu.be" {
+            path
+        } else if host == "youtube.com" || host.ends_with(".youtube.com") {
+            let query = path.strip_prefix("watch?")?;
+            query.split('&').find_map(|param| param.strip_prefix("v="))?
+        } else {
+            return None;
+        };
+        let id = id.split(['?', '&', '#', '/']).next()?;
+        let valid = !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        valid.then(|| id.to_owned())
+    }
 
-        let all = run_command("metadata -a")?;
-        for line in all.lines() {
-            let Some((player,b)) = line.split_once(' ') else {
-                continue;
-            };
-            let b = b.trim_ascii_start();
-            let Some((key,val)) = b.split_once(' ') else {
-                continue;
-            };
-            let val = val.trim_ascii_start();
-            let map = data.entry(player.to_owned()).or_default();
-            match key {
-                "mpris:artUrl" => map.mpris_art_url = Some(urlencoding::decode(&val)?.into_owned()),
-                "mpris:length" => map.mpris_length = Some(val.parse()?),
-                "mpris:trackid" => map.mpris_trackid = Some(val.to_owned()),
-                "xesam:album" => map.xesam_album = Some(val.to_owned()),
-                "xesam:albumArtist" => map.xesam_album_artist = Some(val.to_owned()),
-                "xesam:artist" => map.xesam_artist = Some(val.to_owned()),
-                "xesam:contentCreated" => map.xesam_content_created = Some(val.to_owned()),
-                "xesam:title" => map.xesam_title = Some(val.to_owned()),
-                "xesam:url" => map.xesam_url = Some(urlencoding::decode(&val)?.into_owned()),
-                _ => {}
-            }
-            map.raw.insert(key.to_owned(), val.to_owned());
+    /// Local path of the background art, if it is a `file://` URL.
+    pub fn background_path(&self) -> Option<PathBuf> {
+        file_url_path(&self.background_url()?)
+    }
+
+    /// All art URLs in `raw`, percent-decoded and sorted by key: besides
+    /// `mpris:artUrl` and `mpris:backgroundUrl`, this includes any other key
+    /// ending in `artUrl` or `backgroundUrl`, such as players' extra art
+    /// sizes. Lets richer UIs pick the best available art.
+    ///
+    /// ```
+    /// # use playerctl_rust_wrapper::PlayerMetadata;
+    /// let mut metadata = PlayerMetadata::default();
+    /// metadata.raw.insert("mpris:artUrl".to_owned(), "https://example.com/small.jpg".to_owned());
+    /// metadata.raw.insert("xesam:title".to_owned(), "Song".to_owned());
+    /// assert_eq!(metadata.art_urls(), [("mpris:artUrl", "https://example.com/small.jpg".to_owned())]);
+    /// ```
+    pub fn art_urls(&self) -> Vec<(&str, String)> {
+        let mut urls: Vec<_> = self
+            .raw
+            .iter()
+            .filter(|(key, _)| {
+                let name = key.rsplit(':').next().unwrap_or_default().to_ascii_lowercase();
+                name.ends_with("arturl") || name.ends_with("backgroundurl")
+            })
+            .filter_map(|(key, val)| Some((key.as_str(), decode_url(val)?)))
+            .collect();
+        urls.sort_unstable();
+        urls
+    }
+
+    /// Decode HTML entities in the title and artist.
+    fn decode_html_entities(&mut self) {
+        for field in [&mut self.xesam_title, &mut self.xesam_artist].into_iter().flatten() {
+            *field = decode_html_entities(field);
+        }
+    }
+
+    /// Normalize the whitespace of the title and artist, see
+    /// [`PlayerctlConfig::normalize_whitespace`].
+    fn normalize_whitespace(&mut self) {
+        for field in [&mut self.xesam_title, &mut self.xesam_artist].into_iter().flatten() {
+            // NBSP is whitespace to `split_whitespace` as well.
+            *field = field.split_whitespace().collect::<Vec<_>>().join(" ");
+        }
+    }
+
+    /// Convert a length above [`NANOSECOND_LENGTH_THRESHOLD`] from
+    /// nanoseconds to microseconds.
+    fn fix_nanosecond_length(&mut self) {
+        if let Some(length) = self.mpris_length.filter(|&length| length > duration_micros(NANOSECOND_LENGTH_THRESHOLD)) {
+            #[cfg(feature = "log")]
+            log::warn!("taking implausible length {length} of {:?} as nanoseconds", self.mpris_trackid);
+            self.mpris_length = Some(length / 1000);
+        }
+    }
+
+    /// Percent-decode the URL values in `raw`.
+    fn decode_raw_urls(&mut self) -> Result<()> {
+        for (key, val) in &mut self.raw {
+            *val = decode_value(key, val)?;
+        }
+        Ok(())
+    }
+
+    /// Store a metadata value as printed by playerctl, filling in the typed
+    /// field if the key is known.
+    ///
+    /// Empty values are only stored in `raw`. Repeated keys are collected in
+    /// `raw_multi`.
+    fn insert_value(&mut self, key: &str, val: &str) -> Result<()> {
+        self.insert_raw(key, val);
+        if val.is_empty() {
+            return Ok(());
+        }
+        match key {
+            "mpris:artUrl" => self.mpris_art_url = Some(decode_value(key, val)?),
+            "mpris:length" => self.mpris_length = Some(parse_length(val)?),
+            "mpris:trackid" => self.mpris_trackid = Some(val.to_owned()),
+            "xesam:album" => self.xesam_album = Some(val.to_owned()),
+            "xesam:albumArtist" => self.xesam_album_artist = Some(val.to_owned()),
+            "xesam:artist" => self.xesam_artist = Some(val.to_owned()),
+            "xesam:contentCreated" => self.xesam_content_created = Some(val.to_owned()),
+            "xesam:title" => self.xesam_title = Some(val.to_owned()),
+            "xesam:url" => self.xesam_url = Some(decode_value(key, val)?),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Store a metadata value in `raw` only, collecting repeated keys in
+    /// `raw_multi`.
+    fn insert_raw(&mut self, key: &str, val: &str) {
+        if let Some(previous) = self.raw.insert(key.to_owned(), val.to_owned()) {
+            self.raw_multi
+                .entry(key.to_owned())
+                .or_insert_with(|| vec![previous])
+                .push(val.to_owned());
         }
-        Ok(data)
     }
 }
 
-/// Run a playerctl command.
+/// Options for [`Playerctl::metadata_with_options`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetadataOptions {
+    /// Metadata keys (e.g. `xesam:title`) of the typed fields to populate;
+    /// the other typed fields stay `None`. Defaults to `None`, populating
+    /// all of them.
+    pub fields: Option<Vec<String>>,
+    /// Keep all values in [`PlayerMetadata::raw`]. If `false`, `raw` only
+    /// holds the keys listed in [`fields`](Self::fields). Defaults to
+    /// `true`.
+    pub keep_raw: bool,
+}
+
+impl Default for MetadataOptions {
+    fn default() -> Self {
+        Self { fields: None, keep_raw: true }
+    }
+}
+
+impl MetadataOptions {
+    /// Whether the typed field of `key` is populated.
+    fn populates(&self, key: &str) -> bool {
+        self.fields.as_ref().is_none_or(|fields| fields.iter().any(|field| field == key))
+    }
+}
+
+/// Metadata of all players together with when it was fetched, as returned
+/// by [`Playerctl::metadata_snapshot`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetadataSnapshot {
+    /// When the metadata was fetched, i.e. when playerctl returned it.
+    pub fetched_at: Instant,
+    /// Metadata by player name, like [`Playerctl::metadata`].
+    pub players: HashMap<String, PlayerMetadata>,
+}
+
+impl MetadataSnapshot {
+    /// How long ago the metadata was fetched.
+    pub fn age(&self) -> Duration {
+        self.fetched_at.elapsed()
+    }
+}
+
+/// Status, position and metadata of a player fetched together, as returned
+/// by [`Playerctl::full_state`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FullState {
+    /// The play status.
+    pub status: TrackStatus,
+    /// The position, `None` if the player doesn't report one.
+    pub position: Option<Duration>,
+    /// The metadata, with only the typed fields and their raw values filled
+    /// in.
+    pub metadata: PlayerMetadata,
+}
+
+/// Metadata keys queried by [`Playerctl::full_state`], in order.
+const FULL_STATE_KEYS: [&str; 9] = [
+    "mpris:trackid",
+    "mpris:length",
+    "mpris:artUrl",
+    "xesam:url",
+    "xesam:contentCreated",
+    "xesam:album",
+    "xesam:albumArtist",
+    "xesam:artist",
+    "xesam:title",
+];
+
+/// Delay before each `play` in [`Playerctl::play_uri`].
+pub const PLAY_URI_DELAY: Duration = Duration::from_millis(100);
+
+/// Number of times [`Playerctl::play_uri`] tries to start playback.
+pub const PLAY_URI_ATTEMPTS: usize = 3;
+
+/// Delay before each metadata fetch in [`Playerctl::next_and_metadata`].
+pub const SKIP_METADATA_DELAY: Duration = Duration::from_millis(100);
+
+/// Number of times [`Playerctl::next_and_metadata`] fetches the metadata
+/// waiting for the track to change.
+pub const SKIP_METADATA_ATTEMPTS: usize = 3;
+
+/// How far before the end of the track [`Playerctl::seek_end`] seeks.
+pub const SEEK_END_MARGIN: Duration = Duration::from_millis(500);
+
+/// Lengths above this are taken as nanoseconds if
+/// [`PlayerctlConfig::fix_nanosecond_lengths`] is set.
+pub const NANOSECOND_LENGTH_THRESHOLD: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Exponent of the curve used by [`Playerctl::set_volume_perceptual`].
+pub const PERCEPTUAL_VOLUME_EXPONENT: f64 = 2.;
+
+/// Default threshold for [`Playerctl::smart_previous`].
+pub const SMART_PREVIOUS_THRESHOLD: Duration = Duration::from_secs(3);
+
+/// How often [`Playerctl::play_pause_verified`] checks the status.
+const VERIFY_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How long [`Playerctl::shutdown_daemon`] waits for `playerctld` to exit.
+pub const DAEMON_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How often [`Playerctl::shutdown_daemon`] checks whether `playerctld`
+/// exited.
+const DAEMON_SHUTDOWN_POLL: Duration = Duration::from_millis(50);
+
+/// A changed metadata value, as returned by [`Playerctl::metadata_diff`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetadataChange {
+    /// Name of the typed field (e.g. `xesam_title`) or, for raw values, the
+    /// metadata key (e.g. `xesam:title`).
+    pub field: String,
+    /// Previous value, `None` if the value was added.
+    pub old: Option<String>,
+    /// New value, `None` if the value was removed.
+    pub new: Option<String>,
+}
+
+/// # Playerctl
+///
+/// Playerctl wrapper struct allowing to send commands and control
+/// the player.
+///
+/// # Examples
+///
+/// ## Stop the current player
+///
+/// ```no_run
+/// # use playerctl_rust_wrapper::Playerctl;
+/// Playerctl::stop().unwrap();
+/// ```
+///
+/// ## Advance and rewind 10 seconds
 ///
+/// ```no_run
+/// # use playerctl_rust_wrapper::Playerctl;
+/// Playerctl::position(10.).unwrap();
+/// Playerctl::position(-10.).unwrap();
 /// ```
-/// Playerctl::play().expect("Failed to run command");
+///
+/// ## Control a specific player
+///
+/// ```no_run
+/// # use playerctl_rust_wrapper::Playerctl;
+/// Playerctl::player("mpv").play_pause().unwrap();
 /// ```
-fn run_command(command: &str) -> Result<String> {
-    let args = command.split_whitespace();
-
-    let output = Command::new("playerctl").args(args).output()?;
+///
+/// ## Get metadata
+///
+/// ```no_run
+/// # use playerctl_rust_wrapper::Playerctl;
+/// let metadata = Playerctl::metadata().unwrap();
+///
+/// println!("Track title: {:?}", metadata["mpv"].xesam_title);
+/// ```
+pub struct Playerctl;
 
-    if output.status.success() {
-        Ok(String::from_utf8_lossy(&output.stdout).trim().to_owned())
-    } else {
+impl Playerctl {
+    /// Get a handle controlling the player `name` instead of the default
+    /// player.
+    ///
+    /// The name is passed to `playerctl -p`, so instance-suffixed players
+    /// like `chromium.instance1234` also match `"chromium"`.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::player("mpv").pause().unwrap();
+    /// ```
+    pub fn player(name: impl Into<String>) -> PlayerInstance {
+        PlayerInstance::new(name.into())
+    }
+
+    /// Get a handle bound to the current default player.
+    ///
+    /// The player's full instance name is resolved once, so later commands
+    /// keep going to the same player even if another one becomes the
+    /// default. Fails with [`PlayerctlError::NoPlayer`] if no player is
+    /// running.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// let player = Playerctl::current_player_handle().unwrap();
+    /// player.pause().unwrap();
+    /// player.next().unwrap();
+    /// ```
+    pub fn current_player_handle() -> Result<PlayerInstance> {
+        PlayerctlHandle::default().current_player_handle()
+    }
+
+    /// Check whether the installed playerctl supports `subcommand`, e.g.
+    /// `loop` or `shuffle`, which older versions lack.
+    ///
+    /// This parses the "Available Commands" section of `playerctl --help`
+    /// rather than comparing versions, so it also works for patched or
+    /// unreleased builds. It can't tell whether the player itself supports
+    /// the command. The command list is cached after the first successful
+    /// call for each playerctl binary.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::{LoopStatus, Playerctl};
+    /// if Playerctl::command_exists("loop").unwrap() {
+    ///     Playerctl::set_loop(LoopStatus::Track).unwrap();
+    /// }
+    /// ```
+    pub fn command_exists(subcommand: &str) -> Result<bool> {
+        PlayerctlHandle::default().command_exists(subcommand)
+    }
+
+    /// Get the version of the installed playerctl.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// println!("playerctl {}", Playerctl::version_parsed().unwrap());
+    /// ```
+    pub fn version_parsed() -> Result<Version> {
+        PlayerctlHandle::default().version_parsed()
+    }
+
+    /// Command the player to play.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::play().unwrap();
+    /// ```
+    pub fn play() -> Result<()> {
+        PlayerctlHandle::default().play()
+    }
+
+    /// Command the player to pause.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::pause().unwrap();
+    /// ```
+    pub fn pause() -> Result<()> {
+        PlayerctlHandle::default().pause()
+    }
+
+    /// Command the player to toggle between play/pause.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::play_pause().unwrap();
+    /// ```
+    pub fn play_pause() -> Result<()> {
+        PlayerctlHandle::default().play_pause()
+    }
+
+    /// Toggle between play/pause and make sure the player followed, for
+    /// players that ignore commands sent too soon after they launched.
+    ///
+    /// After `play-pause`, the status is polled for up to `timeout` until
+    /// it changed. If it didn't, `play-pause` is sent once more and polled
+    /// again, so this can take up to twice `timeout` before failing with
+    /// [`PlayerctlError::Unavailable`]. Returns the new status.
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// let status = Playerctl::play_pause_verified(Duration::from_millis(500)).unwrap();
+    /// println!("now {status:?}");
+    /// ```
+    pub fn play_pause_verified(timeout: Duration) -> Result<TrackStatus> {
+        PlayerctlHandle::default().play_pause_verified(timeout)
+    }
+
+    /// Toggle all players together: pause all of them if any is playing,
+    /// otherwise play all of them.
+    ///
+    /// Unlike `playerctl -a play-pause`, which toggles every player on its
+    /// own, this keeps players in sync, so a playing and a paused player
+    /// don't just swap states. Meant for a single global media key.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::play_pause_all().unwrap();
+    /// ```
+    pub fn play_pause_all() -> Result<()> {
+        PlayerctlHandle::default().play_pause_all()
+    }
+
+    /// Command the player to stop.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::stop().unwrap();
+    /// ```
+    pub fn stop() -> Result<()> {
+        PlayerctlHandle::default().stop()
+    }
+
+    /// Command the player to skip to the next track.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::next().unwrap();
+    /// ```
+    pub fn next() -> Result<()> {
+        PlayerctlHandle::default().next()
+    }
+
+    /// Command the player to skip to the previous track.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::previous().unwrap();
+    /// ```
+    pub fn previous() -> Result<()> {
+        PlayerctlHandle::default().previous()
+    }
+
+    /// Skip the default player to the next track, if its MPRIS `CanGoNext`
+    /// property says it can. Skipping is a no-op, for example, on a browser
+    /// tab without a playlist.
+    ///
+    /// Returns whether `next` was issued. This costs two extra calls: one
+    /// to find the default player, and one reading the property from the
+    /// session bus with `dbus-send`, which must be installed. As with
+    /// [`Playerctl::seek_supported`], a player not reporting the property
+    /// is taken as unable, so nothing is issued.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// if !Playerctl::next_if_supported().unwrap() {
+    ///     println!("no next track");
+    /// }
+    /// ```
+    pub fn next_if_supported() -> Result<bool> {
+        PlayerctlHandle::default().next_if_supported()
+    }
+
+    /// Skip the default player to the previous track, if its MPRIS
+    /// `CanGoPrevious` property says it can. See
+    /// [`Playerctl::next_if_supported`].
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// if !Playerctl::previous_if_supported().unwrap() {
+    ///     println!("no previous track");
+    /// }
+    /// ```
+    pub fn previous_if_supported() -> Result<bool> {
+        PlayerctlHandle::default().previous_if_supported()
+    }
+
+    /// Skip to the next track and get its metadata, or `None` if no player
+    /// is running.
+    ///
+    /// Players update their metadata asynchronously, so right after `next`
+    /// the old track may still be reported. This waits [`SKIP_METADATA_DELAY`]
+    /// before fetching and retries until the track id changes, up to
+    /// [`SKIP_METADATA_ATTEMPTS`] times. If it never changes (e.g. on the
+    /// last track), the last fetched metadata is returned.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// if let Some(metadata) = Playerctl::next_and_metadata().unwrap() {
+    ///     println!("Now playing: {:?}", metadata.xesam_title);
+    /// }
+    /// ```
+    pub fn next_and_metadata() -> Result<Option<PlayerMetadata>> {
+        PlayerctlHandle::default().next_and_metadata()
+    }
+
+    /// Skip to the previous track and get its metadata. See
+    /// [`Playerctl::next_and_metadata`].
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// if let Some(metadata) = Playerctl::previous_and_metadata().unwrap() {
+    ///     println!("Now playing: {:?}", metadata.xesam_title);
+    /// }
+    /// ```
+    pub fn previous_and_metadata() -> Result<Option<PlayerMetadata>> {
+        PlayerctlHandle::default().previous_and_metadata()
+    }
+
+    /// Command the player to seek forward/backward OFFSET in seconds. See
+    /// [`Playerctl::seek`] for seeking by a [`SignedDuration`].
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::position(10.).unwrap();
+    /// ```
+    pub fn position(secs: f32) -> Result<()> {
+        PlayerctlHandle::default().position(secs)
+    }
+
+    /// Command the player to seek to the absolute position `secs`.
+    ///
+    /// Fails with [`PlayerctlError::InvalidArgument`] if `secs` is negative.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::set_position(0.).unwrap();
+    /// ```
+    pub fn set_position(secs: f64) -> Result<()> {
+        PlayerctlHandle::default().set_position(secs)
+    }
+
+    /// Command the player `name` to seek to the absolute position `secs`.
+    ///
+    /// Fails with [`PlayerctlError::InvalidArgument`] if `secs` is negative,
+    /// and with [`PlayerctlError::Unavailable`] if the player doesn't
+    /// support seeking.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::set_position_for("mpv", 30.).unwrap();
+    /// ```
+    pub fn set_position_for(name: &str, secs: f64) -> Result<()> {
+        PlayerctlHandle::default().set_position_for(name, secs)
+    }
+
+    /// Restart the current track if more than `threshold` has been played,
+    /// otherwise skip to the previous track, like the "previous" button of
+    /// most media players.
+    ///
+    /// If the position is unknown, this skips to the previous track.
+    /// [`SMART_PREVIOUS_THRESHOLD`] is a good default threshold.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::{Playerctl, SMART_PREVIOUS_THRESHOLD};
+    /// Playerctl::smart_previous(SMART_PREVIOUS_THRESHOLD).unwrap();
+    /// ```
+    pub fn smart_previous(threshold: Duration) -> Result<()> {
+        PlayerctlHandle::default().smart_previous(threshold)
+    }
+
+    /// Seek to the start of the current track.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::seek_start().unwrap();
+    /// ```
+    pub fn seek_start() -> Result<()> {
+        PlayerctlHandle::default().seek_start()
+    }
+
+    /// Seek to [`SEEK_END_MARGIN`] before the end of the current track, so
+    /// it doesn't overshoot into the next track. Fails with
+    /// [`PlayerctlError::Unavailable`] if the track length is unknown.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::seek_end().unwrap();
+    /// ```
+    pub fn seek_end() -> Result<()> {
+        PlayerctlHandle::default().seek_end()
+    }
+
+    /// Seek to the start of the current track of the player `name`.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::seek_start_for("mpv").unwrap();
+    /// ```
+    pub fn seek_start_for(name: &str) -> Result<()> {
+        PlayerctlHandle::default().seek_start_for(name)
+    }
+
+    /// Seek to near the end of the current track of the player `name`. See
+    /// [`Playerctl::seek_end`].
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::seek_end_for("mpv").unwrap();
+    /// ```
+    pub fn seek_end_for(name: &str) -> Result<()> {
+        PlayerctlHandle::default().seek_end_for(name)
+    }
+
+    /// Seek forward by the fraction `frac` of the remaining time, e.g. `0.1`
+    /// to skip 10% of what's left. The target is clamped to the track, so
+    /// negative fractions seek backward but not before the start.
+    ///
+    /// Fails with [`PlayerctlError::InvalidArgument`] if `frac` is NaN and
+    /// with [`PlayerctlError::Unavailable`] if the position or track length
+    /// is unknown.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::seek_by_remaining_fraction(0.1).unwrap();
+    /// ```
+    pub fn seek_by_remaining_fraction(frac: f32) -> Result<()> {
+        PlayerctlHandle::default().seek_by_remaining_fraction(frac)
+    }
+
+    /// Seek to the fraction `frac` of the current track, e.g. `0.5` for the
+    /// middle. `frac` is clamped to 0.0 to 1.0.
+    ///
+    /// Fails with [`PlayerctlError::InvalidArgument`] if `frac` is NaN and
+    /// with [`PlayerctlError::Unavailable`] if the track length is unknown.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::seek_to_percent(0.5).unwrap();
+    /// ```
+    pub fn seek_to_percent(frac: f32) -> Result<()> {
+        PlayerctlHandle::default().seek_to_percent(frac)
+    }
+
+    /// Seek to the fraction `frac` of the current track of the player
+    /// `name`. See [`Playerctl::seek_to_percent`].
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::seek_to_percent_for("mpv", 0.25).unwrap();
+    /// ```
+    pub fn seek_to_percent_for(name: &str, frac: f32) -> Result<()> {
+        PlayerctlHandle::default().seek_to_percent_for(name, frac)
+    }
+
+    /// Skip forward by one "chapter" of fixed length `chapter_len`, without
+    /// going past the end of the track.
+    ///
+    /// This is a heuristic for players without chapter support, not based
+    /// on chapter metadata. Fails with [`PlayerctlError::Unavailable`] if the
+    /// position or track length is unknown.
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::chapter_skip_forward(Duration::from_secs(5 * 60)).unwrap();
+    /// ```
+    pub fn chapter_skip_forward(chapter_len: Duration) -> Result<()> {
+        PlayerctlHandle::default().chapter_skip_forward(chapter_len)
+    }
+
+    /// Skip backward by one "chapter" of fixed length `chapter_len`, without
+    /// going before the start of the track. See
+    /// [`Playerctl::chapter_skip_forward`].
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::chapter_skip_backward(Duration::from_secs(5 * 60)).unwrap();
+    /// ```
+    pub fn chapter_skip_backward(chapter_len: Duration) -> Result<()> {
+        PlayerctlHandle::default().chapter_skip_backward(chapter_len)
+    }
+
+    /// Seek forward/backward by a signed offset such as `"+1:30"`,
+    /// `"-0:45"` or `"+10"`.
+    ///
+    /// The offset is given as `[[hh:]mm:]ss` with a mandatory leading `+` or
+    /// `-`, to distinguish it from an absolute position.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::seek_relative_str("+1:30").unwrap();
+    /// ```
+    pub fn seek_relative_str(offset: &str) -> Result<()> {
+        PlayerctlHandle::default().seek_relative_str(offset)
+    }
+
+    /// Command the player to open `uri`, e.g. a file path or URL.
+    ///
+    /// Paths of existing files, relative to the current directory or
+    /// absolute, are passed as canonical `file://` URLs, since some players
+    /// ignore bare paths. URIs with a scheme (`https://`, `spotify:` ...) and
+    /// paths that don't exist are passed unchanged. Use
+    /// [`Playerctl::open_with`] to skip this.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::open("https://example.com/stream.mp3").unwrap();
+    /// Playerctl::open("music/song.opus").unwrap();
+    /// ```
+    pub fn open(uri: &str) -> Result<()> {
+        PlayerctlHandle::default().open(uri)
+    }
+
+    /// Command the player to open `uri`, turning local paths into `file://`
+    /// URLs like [`Playerctl::open`] if `normalize` is set, or passing `uri`
+    /// exactly as given otherwise.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::open_with("song.opus", false).unwrap();
+    /// ```
+    pub fn open_with(uri: &str, normalize: bool) -> Result<()> {
+        PlayerctlHandle::default().open_with(uri, normalize)
+    }
+
+    /// Open `uri` and start playing it.
+    ///
+    /// Some players load URIs asynchronously and ignore a `play` sent right
+    /// after `open`. So this waits [`PLAY_URI_DELAY`] before each `play`
+    /// and checks the status afterwards, trying up to
+    /// [`PLAY_URI_ATTEMPTS`] times. This is best effort: it doesn't fail if
+    /// the player still isn't playing after the last attempt.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::play_uri("/music/song.opus").unwrap();
+    /// ```
+    pub fn play_uri(uri: &str) -> Result<()> {
+        PlayerctlHandle::default().play_uri(uri)
+    }
+
+    /// Open several URIs in order, e.g. to queue up a folder.
+    ///
+    /// This is not atomic: it stops at the first URI that fails to open and
+    /// returns [`PlayerctlError::OpenFailed`] with the number of URIs opened
+    /// before. Whether the URIs are queued or replace each other depends on
+    /// the player.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::open_many(&["/music/01.opus", "/music/02.opus"]).unwrap();
+    /// ```
+    pub fn open_many(uris: &[&str]) -> Result<()> {
+        PlayerctlHandle::default().open_many(uris)
+    }
+
+    /// Get current player positions, in microseconds.
+    pub fn get_position() -> Result<HashMap<String, u64>> {
+        PlayerctlHandle::default().get_position()
+    }
+
+    /// Get the position of the player `name`.
+    ///
+    /// Returns `None` if the player isn't running or doesn't report a
+    /// position.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// if let Some(position) = Playerctl::get_position_for("mpv").unwrap() {
+    ///     println!("{}s", position.as_secs());
+    /// }
+    /// ```
+    pub fn get_position_for(name: &str) -> Result<Option<Duration>> {
+        PlayerctlHandle::default().get_position_for(name)
+    }
+
+    /// Get the time remaining in the current track of `player`.
+    ///
+    /// Returns `None` when the track length or position is unknown. The
+    /// result is clamped to zero, as the position can briefly exceed the
+    /// length at the end of a track.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// if let Some(remaining) = Playerctl::remaining("mpv").unwrap() {
+    ///     println!("{}s left", remaining.as_secs());
+    /// }
+    /// ```
+    pub fn remaining(player: &str) -> Result<Option<Duration>> {
+        PlayerctlHandle::default().remaining(player)
+    }
+
+    /// Get the progress through the current track of `player`, from 0.0 to
+    /// 1.0.
+    ///
+    /// Returns `None` when the track length is unknown or zero, or the
+    /// position is unknown.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// if let Some(progress) = Playerctl::position_fraction("mpv").unwrap() {
+    ///     println!("{:.0}%", progress * 100.);
+    /// }
+    /// ```
+    pub fn position_fraction(player: &str) -> Result<Option<f32>> {
+        PlayerctlHandle::default().position_fraction(player)
+    }
+
+    /// Get the range `player` can seek in, from zero to the track length,
+    /// e.g. for the bounds of a scrub bar.
+    ///
+    /// Returns `None` if the track length is unknown, so the bar can be
+    /// disabled. Whether the player seeks at all is checked separately
+    /// with [`Playerctl::seek_supported`].
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// if let Some((min, max)) = Playerctl::seek_range("mpv").unwrap() {
+    ///     println!("seekable from {min:?} to {max:?}");
+    /// }
+    /// ```
+    pub fn seek_range(player: &str) -> Result<Option<(Duration, Duration)>> {
+        PlayerctlHandle::default().seek_range(player)
+    }
+
+    /// Get the status, position and metadata of `player` with a single
+    /// playerctl call, so they are consistent with each other, unlike
+    /// separate calls to [`Playerctl::status`], [`Playerctl::get_position_for`]
+    /// and [`Playerctl::metadata_for`], which can straddle a track change.
+    ///
+    /// Only the typed metadata fields are fetched. The `{{status}}` and
+    /// `{{position}}` format variables need playerctl 2.0 or later.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// let state = Playerctl::full_state("mpv").unwrap();
+    /// println!("{:?} at {:?}: {:?}", state.status, state.position, state.metadata.xesam_title);
+    /// ```
+    pub fn full_state(player: &str) -> Result<FullState> {
+        PlayerctlHandle::default().full_state(player)
+    }
+
+    /// Set the volume to LEVEL from 0.0 to 1.0.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::volume(10.).unwrap();
+    /// ```
+    pub fn volume(percent: f32) -> Result<()> {
+        PlayerctlHandle::default().volume(percent)
+    }
+
+    /// Get the volume of the player, from 0.0 to 1.0.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// println!("Volume: {}", Playerctl::get_volume().unwrap());
+    /// ```
+    pub fn get_volume() -> Result<f64> {
+        PlayerctlHandle::default().get_volume()
+    }
+
+    /// Set the volume to the absolute `level`, clamped to 0.0 to 1.0.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::set_volume(0.5).unwrap();
+    /// ```
+    pub fn set_volume(level: f64) -> Result<()> {
+        PlayerctlHandle::default().set_volume(level)
+    }
+
+    /// Get the volume of the player on a perceptual scale from 0.0 to 1.0,
+    /// the inverse of [`Playerctl::set_volume_perceptual`].
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// println!("Slider at {}", Playerctl::get_volume_perceptual().unwrap());
+    /// ```
+    pub fn get_volume_perceptual() -> Result<f64> {
+        PlayerctlHandle::default().get_volume_perceptual()
+    }
+
+    /// Set the volume from a perceptual `level` from 0.0 to 1.0, e.g. the
+    /// position of a volume slider, which feels more even than a linear
+    /// volume. The volume is set to `level` raised to
+    /// [`PERCEPTUAL_VOLUME_EXPONENT`]; use [`Playerctl::set_volume_curve`]
+    /// for another exponent.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// // Sets the volume to 0.25.
+    /// Playerctl::set_volume_perceptual(0.5).unwrap();
+    /// ```
+    pub fn set_volume_perceptual(level: f64) -> Result<()> {
+        PlayerctlHandle::default().set_volume_perceptual(level)
+    }
+
+    /// Get the volume of the player as `volume^(1 / exponent)`, the inverse
+    /// of [`Playerctl::set_volume_curve`].
+    ///
+    /// Fails with [`PlayerctlError::InvalidArgument`] if `exponent` isn't a
+    /// positive number.
+    pub fn get_volume_curve(exponent: f64) -> Result<f64> {
+        PlayerctlHandle::default().get_volume_curve(exponent)
+    }
+
+    /// Set the volume to `level^exponent`, with `level` clamped to 0.0 to
+    /// 1.0. An exponent of 1 sets the volume linearly, larger exponents
+    /// give finer control at low volumes.
+    ///
+    /// Fails with [`PlayerctlError::InvalidArgument`] if `exponent` isn't a
+    /// positive number or `level` isn't a number.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::set_volume_curve(0.5, 3.).unwrap();
+    /// ```
+    pub fn set_volume_curve(level: f64, exponent: f64) -> Result<()> {
+        PlayerctlHandle::default().set_volume_curve(level, exponent)
+    }
+
+    /// Get the volume of the player `name`, from 0.0 to 1.0.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// println!("Browser volume: {}", Playerctl::get_volume_for("firefox").unwrap());
+    /// ```
+    pub fn get_volume_for(name: &str) -> Result<f64> {
+        PlayerctlHandle::default().get_volume_for(name)
+    }
+
+    /// Set the volume of the player `name` to the absolute `level`, clamped
+    /// to 0.0 to 1.0.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::set_volume_for("firefox", 0.2).unwrap();
+    /// ```
+    pub fn set_volume_for(name: &str, level: f64) -> Result<()> {
+        PlayerctlHandle::default().set_volume_for(name, level)
+    }
+
+    /// Change the volume of the player `name` by `delta`, e.g. `-0.1` to
+    /// lower it by 10%. playerctl keeps the result within 0.0 to 1.0.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::adjust_volume_for("firefox", -0.1).unwrap();
+    /// ```
+    pub fn adjust_volume_for(name: &str, delta: f64) -> Result<()> {
+        PlayerctlHandle::default().adjust_volume_for(name, delta)
+    }
+
+    /// List the names of all running players.
+    ///
+    /// Returns an empty list when no players are running.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// for player in Playerctl::list_players().unwrap() {
+    ///     println!("{player}");
+    /// }
+    /// ```
+    pub fn list_players() -> Result<Vec<String>> {
+        PlayerctlHandle::default().list_players()
+    }
+
+    /// List all running players with their status, in playerctl's order,
+    /// using a single playerctl call instead of one per player.
+    ///
+    /// Returns an empty list when no players are running.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// for (player, status) in Playerctl::list_players_with_status().unwrap() {
+    ///     println!("{player}: {status:?}");
+    /// }
+    /// ```
+    pub fn list_players_with_status() -> Result<Vec<(String, TrackStatus)>> {
+        PlayerctlHandle::default().list_players_with_status()
+    }
+
+    /// Wait until a player is running and return its name, checking every
+    /// `poll`. Returns `None` if no player appeared within `timeout`. The
+    /// `playerctld` proxy doesn't count as a player.
+    ///
+    /// Meant for scripts started at login, which may run before any player.
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// match Playerctl::wait_for_player(Duration::from_secs(30), Duration::from_millis(500)).unwrap() {
+    ///     Some(player) => println!("{player} is up"),
+    ///     None => println!("no player started"),
+    /// }
+    /// ```
+    pub fn wait_for_player(timeout: Duration, poll: Duration) -> Result<Option<String>> {
+        PlayerctlHandle::default().wait_for_player(timeout, poll)
+    }
+
+    /// Check that playerctl and the session bus respond, e.g. for a
+    /// watchdog. This lists the players and discards the output; no running
+    /// player counts as healthy, since the bus still answered.
+    ///
+    /// Use [`PlayerctlHandle::ping`] with a
+    /// [`timeout`](PlayerctlConfig::timeout) to fail with
+    /// [`PlayerctlError::Timeout`] instead of hanging on a stuck bus.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// if let Err(e) = Playerctl::ping() {
+    ///     eprintln!("playerctl is unhealthy: {e}");
+    /// }
+    /// ```
+    pub fn ping() -> Result<()> {
+        PlayerctlHandle::default().ping()
+    }
+
+    /// Stop the `playerctld` daemon by running `playerctld shutdown`, and
+    /// wait up to [`DAEMON_SHUTDOWN_TIMEOUT`] for it to leave the player
+    /// list, failing with [`PlayerctlError::Timeout`] if it doesn't.
+    ///
+    /// Returns `Ok(())` right away if the daemon isn't running. This needs
+    /// the `playerctld` binary, which ships with playerctl 2.2 and newer;
+    /// releases without the `shutdown` command fail with
+    /// [`PlayerctlError::CommandError`].
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::shutdown_daemon().unwrap();
+    /// ```
+    pub fn shutdown_daemon() -> Result<()> {
+        PlayerctlHandle::default().shutdown_daemon()
+    }
+
+    /// Check whether commands are routed through the `playerctld` daemon.
+    ///
+    /// While it runs, playerctl picks the default player through the daemon,
+    /// which tracks the most recently active player, rather than taking the
+    /// first player found. The daemon is detected by the `playerctld` proxy
+    /// it adds to the player list.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// if Playerctl::using_daemon().unwrap() {
+    ///     println!("the default player follows the last active one");
+    /// }
+    /// ```
+    pub fn using_daemon() -> Result<bool> {
+        PlayerctlHandle::default().using_daemon()
+    }
+
+    /// Get the number of running players, not counting the `playerctld`
+    /// proxy.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// if Playerctl::active_player_count().unwrap() > 1 {
+    ///     println!("Pick a player");
+    /// }
+    /// ```
+    pub fn active_player_count() -> Result<usize> {
+        PlayerctlHandle::default().active_player_count()
+    }
+
+    /// Check whether a player with the given name is running.
+    ///
+    /// Like `playerctl -p`, a name also matches instance-suffixed players,
+    /// so `"chromium"` matches `"chromium.instance1234"`.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// if Playerctl::player_exists("mpv").unwrap() {
+    ///     println!("mpv is running");
+    /// }
+    /// ```
+    pub fn player_exists(name: &str) -> Result<bool> {
+        PlayerctlHandle::default().player_exists(name)
+    }
+
+    /// Check whether `player` supports seeking, from its MPRIS `CanSeek`
+    /// property. Browsers, for example, often don't.
+    ///
+    /// playerctl doesn't expose the property, so it is read from the session
+    /// bus with `dbus-send`, which must be installed. Like `playerctl -p`,
+    /// `player` also matches instance-suffixed players, so `"chromium"`
+    /// checks the first running `"chromium.instance1234"`. Returns `false`
+    /// if the player doesn't report the property, so a seek bar is only
+    /// enabled when seeking is known to work, and fails with
+    /// [`PlayerctlError::NoPlayer`] if no such player is running.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// if !Playerctl::seek_supported("chromium").unwrap() {
+    ///     println!("disabling the seek bar");
+    /// }
+    /// ```
+    pub fn seek_supported(player: &str) -> Result<bool> {
+        PlayerctlHandle::default().seek_supported(player)
+    }
+
+    /// Get the play status of the player.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::status().unwrap();
+    /// ```
+    pub fn status() -> Result<TrackStatus> {
+        PlayerctlHandle::default().status()
+    }
+
+    /// Get the play status exactly as printed by playerctl, e.g. for
+    /// logging states [`TrackStatus`] doesn't know.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// println!("Status: {}", Playerctl::status_text().unwrap());
+    /// ```
+    pub fn status_text() -> Result<String> {
+        PlayerctlHandle::default().status_text()
+    }
+
+    /// Get the loop status of the player.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// println!("{}", Playerctl::get_loop().unwrap());
+    /// ```
+    pub fn get_loop() -> Result<LoopStatus> {
+        PlayerctlHandle::default().get_loop()
+    }
+
+    /// Set the loop status of the player.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::{LoopStatus, Playerctl};
+    /// Playerctl::set_loop(LoopStatus::Playlist).unwrap();
+    /// ```
+    pub fn set_loop(status: LoopStatus) -> Result<()> {
+        PlayerctlHandle::default().set_loop(status)
+    }
+
+    /// Get the loop status of the player `name`.
+    ///
+    /// Fails with [`PlayerctlError::Unavailable`] if the player is running
+    /// but doesn't support looping, and with [`PlayerctlError::NoPlayer`] if
+    /// it isn't running.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// println!("{}", Playerctl::get_loop_for("mpv").unwrap());
+    /// ```
+    pub fn get_loop_for(name: &str) -> Result<LoopStatus> {
+        PlayerctlHandle::default().get_loop_for(name)
+    }
+
+    /// Set the loop status of the player `name`. See
+    /// [`Playerctl::get_loop_for`] for the errors.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::{LoopStatus, Playerctl};
+    /// Playerctl::set_loop_for("mpv", LoopStatus::Track).unwrap();
+    /// ```
+    pub fn set_loop_for(name: &str, status: LoopStatus) -> Result<()> {
+        PlayerctlHandle::default().set_loop_for(name, status)
+    }
+
+    /// Set the loop status of every player.
+    ///
+    /// Not all players support looping. playerctl keeps going when a player
+    /// fails, so one unsupported player doesn't prevent the others from
+    /// being set; the returned error then holds the messages of all failed
+    /// players.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::{LoopStatus, Playerctl};
+    /// Playerctl::set_loop_all(LoopStatus::Playlist).unwrap();
+    /// ```
+    pub fn set_loop_all(status: LoopStatus) -> Result<()> {
+        PlayerctlHandle::default().set_loop_all(status)
+    }
+
+    /// Get whether the player shuffles.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// println!("Shuffle: {}", Playerctl::get_shuffle().unwrap());
+    /// ```
+    pub fn get_shuffle() -> Result<bool> {
+        PlayerctlHandle::default().get_shuffle()
+    }
+
+    /// Turn shuffle on or off.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::set_shuffle(true).unwrap();
+    /// ```
+    pub fn set_shuffle(shuffle: bool) -> Result<()> {
+        PlayerctlHandle::default().set_shuffle(shuffle)
+    }
+
+    /// Get whether the player `name` shuffles.
+    ///
+    /// Fails with [`PlayerctlError::Unavailable`] if the player is running
+    /// but doesn't support shuffling, and with [`PlayerctlError::NoPlayer`]
+    /// if it isn't running.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// println!("Shuffle: {}", Playerctl::get_shuffle_for("mpv").unwrap());
+    /// ```
+    pub fn get_shuffle_for(name: &str) -> Result<bool> {
+        PlayerctlHandle::default().get_shuffle_for(name)
+    }
+
+    /// Turn shuffle on or off for the player `name`, leaving other players
+    /// alone. See [`Playerctl::get_shuffle_for`] for the errors.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::set_shuffle_for("mpv", true).unwrap();
+    /// ```
+    pub fn set_shuffle_for(name: &str, shuffle: bool) -> Result<()> {
+        PlayerctlHandle::default().set_shuffle_for(name, shuffle)
+    }
+
+    /// Toggle shuffle for the player `name` and return whether it shuffles
+    /// now. See [`Playerctl::get_shuffle_for`] for the errors.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// println!("Shuffle: {}", Playerctl::toggle_shuffle_for("mpv").unwrap());
+    /// ```
+    pub fn toggle_shuffle_for(name: &str) -> Result<bool> {
+        PlayerctlHandle::default().toggle_shuffle_for(name)
+    }
+
+    /// Turn shuffle on or off for every player.
+    ///
+    /// As with [`Playerctl::set_loop_all`], not all players support
+    /// shuffling and a failing player doesn't prevent the others from being
+    /// set.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::set_shuffle_all(false).unwrap();
+    /// ```
+    pub fn set_shuffle_all(shuffle: bool) -> Result<()> {
+        PlayerctlHandle::default().set_shuffle_all(shuffle)
+    }
+
+    /// Get the play status of the player, or `None` if no player is
+    /// running.
+    ///
+    /// Unlike [`Playerctl::status`], this tells "stopped" apart from
+    /// "nothing running".
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// match Playerctl::status_or_none().unwrap() {
+    ///     Some(status) => println!("{status:?}"),
+    ///     None => println!("No player"),
+    /// }
+    /// ```
+    pub fn status_or_none() -> Result<Option<TrackStatus>> {
+        PlayerctlHandle::default().status_or_none()
+    }
+
+    /// Check whether the player is playing. No running player counts as
+    /// not playing.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// if Playerctl::is_playing().unwrap() {
+    ///     println!("Playing");
+    /// }
+    /// ```
+    pub fn is_playing() -> Result<bool> {
+        PlayerctlHandle::default().is_playing()
+    }
+
+    /// Check whether the player is paused. No running player counts as not
+    /// paused.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// if Playerctl::is_paused().unwrap() {
+    ///     println!("Paused");
+    /// }
+    /// ```
+    pub fn is_paused() -> Result<bool> {
+        PlayerctlHandle::default().is_paused()
+    }
+
+    /// Make sure the player is playing, only sending `play` if it isn't
+    /// already.
+    ///
+    /// Fails with [`PlayerctlError::NoPlayer`] if no player is running.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::ensure_playing().unwrap();
+    /// ```
+    pub fn ensure_playing() -> Result<()> {
+        PlayerctlHandle::default().ensure_playing()
+    }
+
+    /// Make sure the player is not playing, only sending `pause` if it is
+    /// currently playing.
+    ///
+    /// Fails with [`PlayerctlError::NoPlayer`] if no player is running.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::ensure_paused().unwrap();
+    /// ```
+    pub fn ensure_paused() -> Result<()> {
+        PlayerctlHandle::default().ensure_paused()
+    }
+
+    /// Seek to the absolute position `secs` and make sure the player is
+    /// playing. Use this when scrubbing should start playback, e.g. after
+    /// picking a position on a seek bar while paused; use
+    /// [`Playerctl::seek_and_keep_state`] to leave a paused player paused.
+    ///
+    /// Fails with [`PlayerctlError::InvalidArgument`] if `secs` is negative.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::seek_and_play(42.).unwrap();
+    /// ```
+    pub fn seek_and_play(secs: f64) -> Result<()> {
+        PlayerctlHandle::default().seek_and_play(secs)
+    }
+
+    /// Seek to the absolute position `secs`, keeping the player playing or
+    /// paused as it was before. Some players start playing when seeking
+    /// while paused, so a paused player is paused again afterwards.
+    ///
+    /// Fails with [`PlayerctlError::InvalidArgument`] if `secs` is negative.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::seek_and_keep_state(42.).unwrap();
+    /// ```
+    pub fn seek_and_keep_state(secs: f64) -> Result<()> {
+        PlayerctlHandle::default().seek_and_keep_state(secs)
+    }
+
+    /// Pause the player while running `f`, e.g. to play a notification
+    /// sound, and resume it afterwards.
+    ///
+    /// The player is only paused (and resumed) if it was playing. It is
+    /// resumed even if `f` panics. No running player is treated like a
+    /// player that is not playing.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::with_paused(|| println!("\x07")).unwrap();
+    /// ```
+    pub fn with_paused<R>(f: impl FnOnce() -> R) -> Result<R> {
+        PlayerctlHandle::default().with_paused(f)
+    }
+
+    /// Render the current track's metadata with a `--format` template.
+    ///
+    /// See the [playerctl documentation](https://github.com/altdesktop/playerctl#printing-properties-and-metadata)
+    /// for the template syntax, or build one with [`FormatTemplate`].
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::{FormatTemplate, Playerctl};
+    /// println!("{}", Playerctl::format("{{artist}} - {{title}}").unwrap());
+    /// println!("{}", Playerctl::format(FormatTemplate::new().field("title")).unwrap());
+    /// ```
+    pub fn format(template: impl AsRef<str>) -> Result<String> {
+        PlayerctlHandle::default().format(template)
+    }
+
+    /// Render `template` for every player in a single playerctl call, keyed
+    /// by player name.
+    ///
+    /// The player name is separated from the rendered text by the ASCII
+    /// unit separator, which doesn't occur in regular metadata. Each player
+    /// must render to a single line, so `template` must not contain
+    /// newlines.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// for (player, line) in Playerctl::format_all("{{artist}} - {{title}}").unwrap() {
+    ///     println!("{player}: {line}");
+    /// }
+    /// ```
+    pub fn format_all(template: impl AsRef<str>) -> Result<HashMap<String, String>> {
+        PlayerctlHandle::default().format_all(template)
+    }
+
+    /// Render the current track with `template` in a single playerctl call,
+    /// e.g. for a status bar. Returns an empty string if no player is
+    /// running; see [`Playerctl::now_playing_line_or`] for another
+    /// fallback.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// println!("{}", Playerctl::now_playing_line("{{artist}} - {{title}}").unwrap());
+    /// ```
+    pub fn now_playing_line(template: impl AsRef<str>) -> Result<String> {
+        PlayerctlHandle::default().now_playing_line(template)
+    }
+
+    /// Like [`Playerctl::now_playing_line`], but returns `fallback` if no
+    /// player is running.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// println!("{}", Playerctl::now_playing_line_or("{{title}}", "Nothing playing").unwrap());
+    /// ```
+    pub fn now_playing_line_or(template: impl AsRef<str>, fallback: &str) -> Result<String> {
+        PlayerctlHandle::default().now_playing_line_or(template, fallback)
+    }
+
+    /// Get a handle whose display helpers render with `template` by
+    /// default. See [`PlayerctlHandle::with_format_default`].
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// let playerctl = Playerctl::with_format_default("{{title}}").unwrap();
+    /// println!("{}", playerctl.display(None::<&str>).unwrap());
+    /// ```
+    pub fn with_format_default(template: impl Into<String>) -> Result<PlayerctlHandle> {
+        PlayerctlHandle::default().with_format_default(template)
+    }
+
+    /// Get a handle running playerctl with `LC_ALL=C`. See
+    /// [`PlayerctlHandle::with_lc_all_c`].
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// println!("{}", Playerctl::with_lc_all_c().display(None::<&str>).unwrap());
+    /// ```
+    pub fn with_lc_all_c() -> PlayerctlHandle {
+        PlayerctlHandle::default().with_lc_all_c()
+    }
+
+    /// Get a handle running every command with `config`.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::{Playerctl, PlayerctlConfig};
+    /// let playerctl = Playerctl::with_config(PlayerctlConfig {
+    ///     binary: "/usr/local/bin/playerctl".into(),
+    ///     ..Default::default()
+    /// });
+    /// println!("{:?}", playerctl.status().unwrap());
+    /// ```
+    pub fn with_config(config: PlayerctlConfig) -> PlayerctlHandle {
+        PlayerctlHandle::default().with_config(config)
+    }
+
+    /// Get a handle calling `hook` with the arguments of every command just
+    /// before it runs. See [`PlayerctlConfig::before_command`].
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// let playerctl = Playerctl::with_before_command_hook(|args| eprintln!("playerctl {args:?}"));
+    /// playerctl.play().unwrap();
+    /// ```
+    pub fn with_before_command_hook(hook: impl Fn(&[&str]) + Send + Sync + 'static) -> PlayerctlHandle {
+        PlayerctlHandle::default().with_before_command_hook(hook)
+    }
+
+    /// Get a handle killing commands running longer than `timeout`. See
+    /// [`PlayerctlConfig::timeout`] and [`PlayerctlHandle::call_timeout`]
+    /// for overriding it for single calls.
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// let metadata = Playerctl::with_timeout(Duration::from_millis(500)).metadata();
+    /// ```
+    pub fn with_timeout(timeout: Duration) -> PlayerctlHandle {
+        PlayerctlHandle::default().with_timeout(timeout)
+    }
+
+    /// Get a handle running playerctl in `dir`. See
+    /// [`PlayerctlHandle::with_working_directory`].
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::with_working_directory("/music").open("album/01.opus").unwrap();
+    /// ```
+    pub fn with_working_directory(dir: impl Into<PathBuf>) -> PlayerctlHandle {
+        PlayerctlHandle::default().with_working_directory(dir)
+    }
+
+    /// Get a handle running playerctl with the given niceness. See
+    /// [`PlayerctlHandle::with_spawn_niceness`].
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// println!("{:?}", Playerctl::with_spawn_niceness(10).status().unwrap());
+    /// ```
+    #[cfg(unix)]
+    pub fn with_spawn_niceness(niceness: i32) -> PlayerctlHandle {
+        PlayerctlHandle::default().with_spawn_niceness(niceness)
+    }
+
+    /// Get a handle passing the stderr output of its commands to `callback`.
+    /// See [`PlayerctlConfig::stderr_callback`].
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// let playerctl = Playerctl::with_stderr_callback(|stderr| eprintln!("playerctl: {stderr}"));
+    /// playerctl.play().unwrap();
+    /// ```
+    pub fn with_stderr_callback(callback: impl Fn(&str) + Send + Sync + 'static) -> PlayerctlHandle {
+        PlayerctlHandle::default().with_stderr_callback(callback)
+    }
+
+    /// Get metadata of all active players as generic maps, keyed by player
+    /// name and then metadata key (e.g. `xesam:title`).
+    ///
+    /// Unlike [`Playerctl::metadata`], this exposes all keys uniformly. URL
+    /// values (`mpris:artUrl`, `xesam:url`) are percent-decoded.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// let metadata = Playerctl::metadata_as_map_of_maps().unwrap();
+    /// println!("{:?}", metadata["mpv"].get("xesam:title"));
+    /// ```
+    pub fn metadata_as_map_of_maps() -> Result<HashMap<String, HashMap<String, String>>> {
+        PlayerctlHandle::default().metadata_as_map_of_maps()
+    }
+
+    /// Compare two metadata values and list what changed.
+    ///
+    /// Typed fields come first, in declaration order, followed by changed
+    /// `raw` values sorted by key. Identical metadata yields an empty list.
+    ///
+    /// ```
+    /// # use playerctl_rust_wrapper::{PlayerMetadata, Playerctl};
+    /// let old = PlayerMetadata { xesam_title: Some("A".to_owned()), ..Default::default() };
+    /// let new = PlayerMetadata { xesam_title: Some("B".to_owned()), ..Default::default() };
+    /// let changes = Playerctl::metadata_diff(&old, &new);
+    /// assert_eq!(changes[0].field, "xesam_title");
+    /// assert_eq!(changes[0].new.as_deref(), Some("B"));
+    /// ```
+    pub fn metadata_diff(old: &PlayerMetadata, new: &PlayerMetadata) -> Vec<MetadataChange> {
+        let typed = old
+            .typed_fields()
+            .into_iter()
+            .zip(new.typed_fields())
+            .filter(|((_, old), (_, new))| old != new)
+            .map(|((field, old), (_, new))| MetadataChange { field: field.to_owned(), old, new });
+
+        let mut keys: Vec<_> = old.raw.keys().chain(new.raw.keys()).collect();
+        keys.sort();
+        keys.dedup();
+        let raw = keys
+            .into_iter()
+            .filter(|key| old.raw.get(*key) != new.raw.get(*key))
+            .map(|key| MetadataChange {
+                field: key.clone(),
+                old: old.raw.get(key).cloned(),
+                new: new.raw.get(key).cloned(),
+            });
+
+        typed.chain(raw).collect()
+    }
+
+    /// Get metadata information for all active players.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// let metadata = Playerctl::metadata().unwrap();
+    ///
+    /// println!("Title: {:?}", metadata.values().next().unwrap().xesam_title);
+    /// ```
+    pub fn metadata() -> Result<HashMap<String, PlayerMetadata>> {
+        PlayerctlHandle::default().metadata()
+    }
+
+    /// Get metadata information for all active players like
+    /// [`Playerctl::metadata`], stamped with the time it was fetched, for
+    /// caches and UIs deciding whether it is stale.
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// let snapshot = Playerctl::metadata_snapshot().unwrap();
+    /// if snapshot.age() > Duration::from_secs(1) {
+    ///     println!("stale metadata of {} players", snapshot.players.len());
+    /// }
+    /// ```
+    pub fn metadata_snapshot() -> Result<MetadataSnapshot> {
+        PlayerctlHandle::default().metadata_snapshot()
+    }
+
+    /// Get metadata information for all active players like
+    /// [`Playerctl::metadata`], only populating the typed fields listed in
+    /// `options`.
+    ///
+    /// Skipping fields saves parsing and allocations in hot polling loops.
+    /// [`MetadataOptions::default`] populates everything, like
+    /// [`Playerctl::metadata`].
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::{MetadataOptions, Playerctl};
+    /// let options = MetadataOptions {
+    ///     fields: Some(vec!["xesam:artist".to_owned(), "xesam:title".to_owned()]),
+    ///     keep_raw: false,
+    /// };
+    /// for (player, metadata) in Playerctl::metadata_with_options(&options).unwrap() {
+    ///     println!("{player}: {:?} - {:?}", metadata.xesam_artist, metadata.xesam_title);
+    /// }
+    /// ```
+    pub fn metadata_with_options(options: &MetadataOptions) -> Result<HashMap<String, PlayerMetadata>> {
+        PlayerctlHandle::default().metadata_with_options(options)
+    }
+
+    /// Get metadata information for all active players like
+    /// [`Playerctl::metadata`], but skipping values that fail to parse
+    /// instead of failing the whole call.
+    ///
+    /// A skipped value leaves its typed field `None` and is kept in `raw`.
+    /// For each, a warning naming the player, key and reason is returned
+    /// alongside the metadata, to find out why a field is missing.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// let (metadata, warnings) = Playerctl::metadata_with_warnings().unwrap();
+    /// for warning in warnings {
+    ///     eprintln!("{warning}");
+    /// }
+    /// ```
+    pub fn metadata_with_warnings() -> Result<(HashMap<String, PlayerMetadata>, Vec<String>)> {
+        PlayerctlHandle::default().metadata_with_warnings()
+    }
+
+    /// Get metadata information for all active players, requesting just the
+    /// typed fields with a single delimited `--format` query.
+    ///
+    /// Unlike [`Playerctl::metadata`], this doesn't depend on the layout of
+    /// playerctl's free-form output, so values containing newlines can't be
+    /// mistaken for other keys, and less output is parsed. The tradeoff is
+    /// that `raw` only contains the typed fields' keys; use
+    /// [`Playerctl::metadata`] when other keys are needed. See
+    /// `examples/metadata_bench.rs` for a comparison of both.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// for (player, metadata) in Playerctl::metadata_fast().unwrap() {
+    ///     println!("{player}: {:?}", metadata.xesam_title);
+    /// }
+    /// ```
+    pub fn metadata_fast() -> Result<HashMap<String, PlayerMetadata>> {
+        PlayerctlHandle::default().metadata_fast()
+    }
+
+    /// Get the metadata of the player `name`.
+    ///
+    /// Fails with [`PlayerctlError::NoPlayer`] if the player isn't running.
+    /// A running player without a loaded track yields empty metadata, so
+    /// UIs can tell whether to show the player at all. playerctl reports
+    /// both cases alike, so on [`PlayerctlError::NoPlayer`] this checks
+    /// with [`Playerctl::player_exists`] whether the player is running.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// let metadata = Playerctl::metadata_for("mpv").unwrap();
+    /// println!("{:?}", metadata.xesam_title);
+    /// ```
+    pub fn metadata_for(name: &str) -> Result<PlayerMetadata> {
+        PlayerctlHandle::default().metadata_for(name)
+    }
+
+    /// Get the metadata of the default player, failing with
+    /// [`PlayerctlError::NoPlayer`] if no player is running.
+    ///
+    /// For scripts that assume a player exists; see
+    /// [`Playerctl::first_available_metadata`] for a variant returning
+    /// `None` instead.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// let metadata = Playerctl::metadata_current().unwrap();
+    /// println!("{:?}", metadata.xesam_title);
+    /// ```
+    pub fn metadata_current() -> Result<PlayerMetadata> {
+        PlayerctlHandle::default().metadata_current()
+    }
+
+    /// Get the metadata of each of the players `names`, like
+    /// [`Playerctl::metadata_for`], fetching up to `threads` players at once.
+    ///
+    /// With one playerctl call per player, fetching many players in
+    /// sequence adds up; this spawns `threads` scoped worker threads, capped
+    /// at the number of players, that end before this returns. With
+    /// `threads` at most 1, the players are fetched in sequence on the
+    /// calling thread. Errors are reported per player, so one failing
+    /// player doesn't hide the metadata of the others.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// let players = Playerctl::list_players().unwrap();
+    /// let names: Vec<_> = players.iter().map(String::as_str).collect();
+    /// for (player, metadata) in Playerctl::metadata_for_many(&names, 4) {
+    ///     println!("{player}: {:?}", metadata.map(|m| m.xesam_title));
+    /// }
+    /// ```
+    pub fn metadata_for_many(names: &[&str], threads: usize) -> HashMap<String, Result<PlayerMetadata>> {
+        PlayerctlHandle::default().metadata_for_many(names, threads)
+    }
+
+    /// Get the metadata of the first running player of `players`, in order
+    /// of priority. `%any` matches any player, so `["spotify", "%any"]`
+    /// prefers Spotify and falls back to whatever else is running.
+    ///
+    /// Returns `None` if none of the players is running. Fails with
+    /// [`PlayerctlError::InvalidArgument`] if a name contains a comma.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// if let Some(metadata) = Playerctl::first_available_metadata(&["spotify", "%any"]).unwrap() {
+    ///     println!("{:?}", metadata.xesam_title);
+    /// }
+    /// ```
+    pub fn first_available_metadata(players: &[&str]) -> Result<Option<PlayerMetadata>> {
+        PlayerctlHandle::default().first_available_metadata(players)
+    }
+}
+
+/// Call `fetch` for each of `names` on up to `threads` scoped threads, or
+/// on the calling thread if `threads` is at most 1, and collect the results
+/// by name.
+fn fetch_each<T: Send>(names: &[&str], threads: usize, fetch: impl Fn(&str) -> T + Sync) -> HashMap<String, T> {
+    let threads = threads.min(names.len());
+    if threads <= 1 {
+        return names.iter().map(|&name| (name.to_owned(), fetch(name))).collect();
+    }
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(HashMap::with_capacity(names.len()));
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| {
+                while let Some(&name) = names.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    let result = fetch(name);
+                    results.lock().unwrap_or_else(|e| e.into_inner()).insert(name.to_owned(), result);
+                }
+            });
+        }
+    });
+    results.into_inner().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Get the subcommands listed in the "Available Commands" section of
+/// `playerctl --help`.
+fn help_commands(help: &str) -> Vec<String> {
+    help.lines()
+        .skip_while(|line| line.trim() != "Available Commands:")
+        .skip(1)
+        .take_while(|line| line.starts_with(char::is_whitespace))
+        .filter_map(|line| line.split_whitespace().next())
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Get the metadata of the default player with `config`, or `None` if no
+/// player is running.
+fn current_metadata(config: &PlayerctlConfig) -> Result<Option<PlayerMetadata>> {
+    match run_raw_with(&["metadata"], config) {
+        Ok(output) => {
+            let mut metadata = parse_metadata(&output)?.into_values().next();
+            if let Some(metadata) = &mut metadata {
+                config.postprocess(metadata)?;
+            }
+            Ok(metadata)
+        }
+        Err(PlayerctlError::NoPlayer) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Run `skip` on `handle` and fetch the metadata of the new track, see
+/// [`Playerctl::next_and_metadata`].
+fn skip_and_metadata(
+    handle: &PlayerctlHandle,
+    skip: fn(&PlayerctlHandle) -> Result<()>,
+) -> Result<Option<PlayerMetadata>> {
+    let Some(before) = current_metadata(handle.config())? else {
+        return Ok(None);
+    };
+    skip(handle)?;
+    let mut metadata = None;
+    for _ in 0..SKIP_METADATA_ATTEMPTS {
+        thread::sleep(SKIP_METADATA_DELAY);
+        metadata = current_metadata(handle.config())?;
+        if metadata.as_ref().is_none_or(|after| after.mpris_trackid != before.mpris_trackid) {
+            break;
+        }
+    }
+    Ok(metadata)
+}
+
+/// Run `command` on the default player of `handle` if its MPRIS property
+/// `capability` is `true`, returning whether it ran.
+fn skip_if_supported(handle: &PlayerctlHandle, command: &str, capability: &str) -> Result<bool> {
+    let player = handle.current_player_handle()?;
+    if !player_capability(player.name(), capability, handle.config())? {
+        return Ok(false);
+    }
+    // Target the player checked, even if the default changed meanwhile.
+    run_args_with(&["-p", player.name(), command], handle.config())?;
+    Ok(true)
+}
+
+/// List the running players with `config`, see [`Playerctl::list_players`].
+fn list_players_with(config: &PlayerctlConfig) -> Result<Vec<String>> {
+    match run_lines_with(&["-l"], config) {
+        Ok(players) => Ok(players),
+        Err(PlayerctlError::NoPlayer) => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Get the metadata of all players with `config`, see
+/// [`Playerctl::metadata`].
+fn metadata_with(config: &PlayerctlConfig) -> Result<HashMap<String, PlayerMetadata>> {
+    let mut metadata = parse_metadata(&run_raw_with(&["metadata", "-a"], config)?)?;
+    for player in metadata.values_mut() {
+        config.postprocess(player)?;
+    }
+    Ok(metadata)
+}
+
+/// Get the typed metadata fields of all players with `config`, see
+/// [`Playerctl::metadata_fast`].
+fn metadata_fast_with(config: &PlayerctlConfig) -> Result<HashMap<String, PlayerMetadata>> {
+    let mut m = HashMap::new();
+    for line in run_lines_with(&["-a", "metadata", "--format", &follow::follow_template()], config)? {
+        if let Some((player, mut metadata)) = follow::parse_fields(&line)? {
+            config.postprocess(&mut metadata)?;
+            m.insert(player, metadata);
+        }
+    }
+    Ok(m)
+}
+
+/// Get the metadata of the player `name` with `config`, see
+/// [`Playerctl::metadata_for`].
+fn metadata_for_with(name: &str, config: &PlayerctlConfig) -> Result<PlayerMetadata> {
+    let mut metadata = match run_raw_with(&["-p", name, "metadata"], config) {
+        Ok(output) => parse_metadata(&output)?.into_values().next().unwrap_or_default(),
+        Err(PlayerctlError::NoPlayer)
+            if list_players_with(config)?.iter().any(|player| player_matches(player, name)) =>
+        {
+            PlayerMetadata::default()
+        }
+        Err(e) => return Err(e),
+    };
+    config.postprocess(&mut metadata)?;
+    Ok(metadata)
+}
+
+/// Get the status, position and metadata of `player` with `config`, see
+/// [`Playerctl::full_state`].
+fn full_state_with(player: &str, config: &PlayerctlConfig) -> Result<FullState> {
+    let format = ["status", "position"]
+        .iter()
+        .chain(&FULL_STATE_KEYS)
+        .map(|key| format!("{{{{{key}}}}}"))
+        .collect::<Vec<_>>()
+        .join(&follow::SEPARATOR.to_string());
+    let mut state = parse_full_state(&run_args_with(&["-p", player, "metadata", "--format", &format], config)?)?;
+    config.postprocess(&mut state.metadata)?;
+    Ok(state)
+}
+
+/// Read the boolean MPRIS player property `property` (e.g. `CanSeek`) of
+/// `player` with `dbus-send`, run with the options of `config`. `player` is
+/// the full bus name suffix, including any instance. A property the player
+/// doesn't report is taken as `false`.
+fn player_capability(player: &str, property: &str, config: &PlayerctlConfig) -> Result<bool> {
+    let config = PlayerctlConfig {
+        binary: PathBuf::from("dbus-send"),
+        ..config.clone()
+    };
+    let dest = format!("--dest=org.mpris.MediaPlayer2.{player}");
+    let property = format!("string:{property}");
+    let args = [
+        "--session",
+        "--print-reply",
+        &dest,
+        "/org/mpris/MediaPlayer2",
+        "org.freedesktop.DBus.Properties.Get",
+        "string:org.mpris.MediaPlayer2.Player",
+        &property,
+    ];
+    match run_args_with(&args, &config) {
+        Ok(reply) => parse_dbus_bool(&reply),
+        Err(PlayerctlError::CommandError(e)) if e.contains("UnknownProperty") || e.contains("InvalidArgs") => {
+            Ok(false)
+        }
+        // The player quit since it was listed.
+        Err(PlayerctlError::CommandError(e)) if e.contains("ServiceUnknown") => Err(PlayerctlError::NoPlayer),
+        Err(e) => Err(e),
+    }
+}
+
+/// Parse the `boolean` value of a `dbus-send --print-reply` reply.
+fn parse_dbus_bool(reply: &str) -> Result<bool> {
+    match reply.split_whitespace().skip_while(|word| *word != "boolean").nth(1) {
+        Some("true") => Ok(true),
+        Some("false") => Ok(false),
+        _ => Err(PlayerctlError::Other(format!("expected a boolean D-Bus reply, got {reply:?}"))),
+    }
+}
+
+/// Check whether `players` contains the `playerctld` proxy.
+fn lists_daemon(players: &[String]) -> bool {
+    players.iter().any(|player| is_daemon(player))
+}
+
+/// Whether `player` is the `playerctld` proxy rather than a real player.
+fn is_daemon(player: &str) -> bool {
+    player_matches(player, "playerctld")
+}
+
+/// Tell apart the failure of a `loop` or `shuffle` command for the player
+/// `name`: playerctl reports a player not supporting the playback `mode`
+/// like a missing player, so the player list is checked with `config`.
+fn mode_result(name: &str, mode: &str, result: Result<String>, config: &PlayerctlConfig) -> Result<String> {
+    match result {
+        Err(PlayerctlError::NoPlayer)
+            if list_players_with(config)?.iter().any(|player| player_matches(player, name)) =>
+        {
+            Err(PlayerctlError::Unavailable(format!("{name} doesn't support {mode}")))
+        }
+        result => result,
+    }
+}
+
+/// Turn the result of the `-l` command run by [`Playerctl::ping`] into its
+/// health status.
+fn ping_result(result: Result<String>) -> Result<()> {
+    match result {
+        Ok(_) | Err(PlayerctlError::NoPlayer) => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+/// The command [`Playerctl::play_pause_all`] sends to all players with the
+/// given statuses.
+fn play_pause_all_command(statuses: &[TrackStatus]) -> &'static str {
+    if statuses.contains(&TrackStatus::Playing) {
+        "pause"
+    } else {
+        "play"
+    }
+}
+
+/// Parse the output of `playerctl status`.
+fn parse_status(status: &str) -> TrackStatus {
+    match status.trim() {
+        "Playing" => TrackStatus::Playing,
+        "Paused" => TrackStatus::Paused,
+        _ => TrackStatus::Stopped,
+    }
+}
+
+/// Parse the output of `playerctl metadata -a`.
+///
+/// Each line has the shape `player key value`. Lines not matching this shape
+/// continue the value of the previous line, as values like `xesam:comment`
+/// may contain newlines.
+fn parse_metadata(output: &str) -> Result<HashMap<String, PlayerMetadata>> {
+    parse_metadata_with(output, &MetadataOptions::default(), None)
+}
+
+/// Parse `playerctl metadata` output, populating the fields selected by
+/// `options`. With `warnings`, values that fail to parse are reported there
+/// and skipped, instead of failing.
+fn parse_metadata_with(
+    output: &str,
+    options: &MetadataOptions,
+    mut warnings: Option<&mut Vec<String>>,
+) -> Result<HashMap<String, PlayerMetadata>> {
+    let mut entries: Vec<(&str, &str, String)> = Vec::new();
+    for line in output.lines() {
+        let record = line.trim_end().split_once(' ').and_then(|(player, b)| {
+            let b = b.trim_ascii_start();
+            // A key without value is an empty trailing field.
+            let (key, val) = b.split_once(' ').unwrap_or((b, ""));
+            is_metadata_key(key).then(|| (player, key, val.trim_ascii_start()))
+        });
+        match (record, entries.last_mut()) {
+            (Some((player, key, val)), _) => entries.push((player, key, val.to_owned())),
+            (None, Some((_, _, val))) => {
+                val.push('\n');
+                val.push_str(line);
+            }
+            (None, None) => {}
+        }
+    }
+
+    let mut data: HashMap<_, PlayerMetadata> = HashMap::new();
+    for (player, key, val) in entries {
+        // Blank lines after the last line of a value are not part of it.
+        let val = val.trim_end_matches('\n');
+        let metadata = data.entry(player.to_owned()).or_default();
+        if options.populates(key) {
+            match (metadata.insert_value(key, val), warnings.as_deref_mut()) {
+                (Err(e), Some(warnings)) => warnings.push(format!("{player}: {key}: {e}")),
+                (result, _) => result?,
+            }
+        } else if options.keep_raw {
+            metadata.insert_raw(key, val);
+        }
+    }
+    Ok(data)
+}
+
+/// Metadata keys holding percent-encoded URLs.
+const URL_KEYS: [&str; 2] = ["mpris:artUrl", "xesam:url"];
+
+/// Parse the output of the [`Playerctl::full_state`] query.
+fn parse_full_state(output: &str) -> Result<FullState> {
+    let mut values = output.split(follow::SEPARATOR);
+    let status = parse_status(values.next().unwrap_or_default());
+    let position = match values.next().unwrap_or_default().trim() {
+        "" => None,
+        position => Some(Duration::from_micros(position.parse()?)),
+    };
+    let mut metadata = PlayerMetadata::default();
+    for (key, val) in FULL_STATE_KEYS.iter().zip(values) {
+        if !val.is_empty() {
+            metadata.insert_value(key, val)?;
+        }
+    }
+    Ok(FullState { status, position, metadata })
+}
+
+/// Parse an `mpris:length` value in microseconds.
+///
+/// Besides plain integers (`123`), this accepts a trailing unit word as
+/// emitted by a few players (`123 us`, `123µs`); the unit is ignored, the
+/// value is always taken as microseconds.
+fn parse_length(val: &str) -> std::result::Result<u64, ParseIntError> {
+    let val = val.trim();
+    val.parse().or_else(|e| {
+        let number = val.trim_end_matches(char::is_alphabetic).trim_end();
+        if number.len() < val.len() {
+            number.parse()
+        } else {
+            Err(e)
+        }
+    })
+}
+
+/// Decode a raw metadata value: percent-decode URL values, keep others
+/// as-is.
+fn decode_value(key: &str, val: &str) -> Result<String> {
+    if URL_KEYS.contains(&key) {
+        Ok(urlencoding::decode(val)?.into_owned())
+    } else {
+        Ok(val.to_owned())
+    }
+}
+
+/// Check whether `key` looks like a metadata key, e.g. `xesam:title`.
+fn is_metadata_key(key: &str) -> bool {
+    let is_part = |part: &str| !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    key.split_once(':')
+        .is_some_and(|(namespace, name)| is_part(namespace) && is_part(name))
+}
+
+/// Decode the named entities `&amp;`, `&lt;`, `&gt;`, `&quot;`, `&apos;`
+/// and decimal or hexadecimal numeric entities. Unknown or malformed
+/// entities are kept as-is.
+fn decode_html_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity = rest[1..].split_once(';').and_then(|(name, _)| {
+            let c = match name {
+                "amp" => '&',
+                "lt" => '<',
+                "gt" => '>',
+                "quot" => '"',
+                "apos" => '\'',
+                _ => {
+                    let code = match name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                        None => name.strip_prefix('#')?.parse().ok()?,
+                    };
+                    char::from_u32(code)?
+                }
+            };
+            Some((c, name.len() + 2))
+        });
+        match entity {
+            Some((c, len)) => {
+                decoded.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+/// Percent-decode a non-empty URL, or `None` if it is empty or doesn't
+/// decode to UTF-8.
+fn decode_url(url: &str) -> Option<String> {
+    let url = url.trim();
+    if url.is_empty() {
+        return None;
+    }
+    urlencoding::decode(url).ok().map(|url| url.into_owned())
+}
+
+/// Convert a decoded `file://` URL to a local path.
+fn file_url_path(url: &str) -> Option<PathBuf> {
+    url.strip_prefix("file://")
+        .filter(|path| path.starts_with('/'))
+        .map(PathBuf::from)
+}
+
+/// Turn `uri` into a canonical `file://` URL if it is the path of an
+/// existing file, see [`Playerctl::open`]. Relative paths resolve against
+/// `dir` if given, otherwise against the current directory.
+fn normalize_uri(uri: &str, dir: Option<&Path>) -> String {
+    if has_scheme(uri) {
+        return uri.to_owned();
+    }
+    let path = match dir {
+        Some(dir) => dir.join(uri),
+        None => PathBuf::from(uri),
+    };
+    match fs::canonicalize(path) {
+        Ok(path) => file_url(&path.to_string_lossy()),
+        Err(_) => uri.to_owned(),
+    }
+}
+
+/// Check whether `uri` starts with a URI scheme like `https:`. Single
+/// letters are not taken as scheme, so Windows drive letters aren't either.
+fn has_scheme(uri: &str) -> bool {
+    uri.split_once(':').is_some_and(|(scheme, _)| {
+        scheme.len() > 1
+            && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme.chars().all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+    })
+}
+
+/// Build the `file://` URL of an absolute `path`, percent-encoding each
+/// component.
+fn file_url(path: &str) -> String {
+    let components: Vec<_> = path.split('/').map(|part| urlencoding::encode(part)).collect();
+    format!("file://{}", components.join("/"))
+}
+
+/// Check whether a player name matches `name`, either exactly or as an
+/// instance of it (`name.instance...`).
+fn player_matches(player: &str, name: &str) -> bool {
+    player
+        .strip_prefix(name)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+}
+
+/// Format an absolute volume level as a `volume` argument, clamped to
+/// 0.0 to 1.0.
+fn volume_level(level: f64) -> Result<String> {
+    if level.is_nan() {
+        return Err(PlayerctlError::InvalidArgument("volume must be a number".to_owned()));
+    }
+    Ok(level.clamp(0., 1.).to_string())
+}
+
+/// Check that a volume curve exponent is a positive number.
+fn volume_exponent(exponent: f64) -> Result<f64> {
+    if exponent.is_finite() && exponent > 0. {
+        Ok(exponent)
+    } else {
+        Err(PlayerctlError::InvalidArgument(format!("volume exponent must be positive: {exponent}")))
+    }
+}
+
+/// Parse the output of `playerctl volume`.
+fn parse_volume(output: &str) -> Result<f64> {
+    output
+        .trim()
+        .parse()
+        .map_err(|_| PlayerctlError::Other(format!("invalid volume {output:?}")))
+}
+
+/// The `shuffle` argument for turning shuffle on or off.
+fn shuffle_arg(shuffle: bool) -> &'static str {
+    if shuffle { "On" } else { "Off" }
+}
+
+/// Parse the output of `playerctl shuffle`.
+fn parse_shuffle(output: &str) -> Result<bool> {
+    match output.trim() {
+        "On" => Ok(true),
+        "Off" => Ok(false),
+        other => Err(PlayerctlError::Other(format!("unknown shuffle status {other:?}"))),
+    }
+}
+
+/// A duration in whole microseconds, saturating at `u64::MAX`.
+fn duration_micros(duration: Duration) -> u64 {
+    duration.as_micros().try_into().unwrap_or(u64::MAX)
+}
+
+/// Format an absolute position in seconds as a `position` argument.
+fn absolute_position(secs: f64) -> Result<String> {
+    if secs.is_nan() || secs < 0. {
+        return Err(PlayerctlError::InvalidArgument(format!(
+            "position must not be negative, got {secs}"
+        )));
+    }
+    Ok(secs.to_string())
+}
+
+/// Parse a signed `[[hh:]mm:]ss` offset into seconds.
+fn parse_relative_offset(offset: &str) -> Result<f32> {
+    let invalid = || PlayerctlError::InvalidArgument(format!("invalid relative offset {offset:?}"));
+    let (sign, time) = match offset.trim().split_at_checked(1) {
+        Some(("+", time)) => (1., time),
+        Some(("-", time)) => (-1., time),
+        _ => return Err(invalid()),
+    };
+    let parts: Vec<_> = time.split(':').collect();
+    if parts.len() > 3 {
+        return Err(invalid());
+    }
+    let mut secs = 0.;
+    for (i, part) in parts.iter().enumerate() {
+        // Only the seconds may be fractional.
+        let valid = !part.is_empty()
+            && part.chars().all(|c| c.is_ascii_digit() || (c == '.' && i == parts.len() - 1));
+        let value: f32 = part.parse().ok().filter(|_| valid).ok_or_else(invalid)?;
+        if i > 0 && value >= 60. {
+            return Err(invalid());
+        }
+        secs = secs * 60. + value;
+    }
+    Ok(sign * secs)
+}
+
+/// The position in microseconds `frac` of the remaining time after
+/// `position`, clamped to `0..=length`.
+fn remaining_fraction_target(position: u64, length: u64, frac: f32) -> u64 {
+    let remaining = length.saturating_sub(position) as f64;
+    (position as f64 + f64::from(frac) * remaining).clamp(0., length as f64) as u64
+}
+
+/// Fetch the known position and track length in microseconds, failing
+/// with [`PlayerctlError::Unavailable`] if either is unknown.
+fn known_position_and_length(target: &[&str], config: &PlayerctlConfig) -> Result<(u64, u64)> {
+    match position_and_length_args(target, config)? {
+        (Some(position), Some(length)) => Ok((position, length)),
+        (None, _) => Err(PlayerctlError::Unavailable("position is unknown".to_owned())),
+        (_, None) => Err(PlayerctlError::Unavailable("track length is unknown".to_owned())),
+    }
+}
+
+/// The position in seconds at the fraction `frac` of the track, clamped to
+/// 0.0 to 1.0, passing `target` before the subcommand.
+fn percent_position(target: &[&str], frac: f32, config: &PlayerctlConfig) -> Result<f64> {
+    if frac.is_nan() {
+        return Err(PlayerctlError::InvalidArgument("fraction must be a number".to_owned()));
+    }
+    let (_, length) = position_and_length_args(target, config)?;
+    let length = length.ok_or_else(|| PlayerctlError::Unavailable("track length is unknown".to_owned()))?;
+    Ok(Duration::from_micros(length).as_secs_f64() * f64::from(frac.clamp(0., 1.)))
+}
+
+/// The position in seconds [`Playerctl::seek_end`] seeks to, passing
+/// `target` before the subcommand.
+fn end_position(target: &[&str], config: &PlayerctlConfig) -> Result<f64> {
+    let (_, length) = position_and_length_args(target, config)?;
+    let length = length.ok_or_else(|| PlayerctlError::Unavailable("track length is unknown".to_owned()))?;
+    Ok(Duration::from_micros(length.saturating_sub(duration_micros(SEEK_END_MARGIN))).as_secs_f64())
+}
+
+/// Fetch the position and track length of `player` in microseconds, in a
+/// single call with `config`.
+fn position_and_length(player: &str, config: &PlayerctlConfig) -> Result<(Option<u64>, Option<u64>)> {
+    position_and_length_args(&["-p", player], config)
+}
+
+/// Fetch the position and track length in microseconds with `config`,
+/// passing `target` (e.g. `-p name`, or nothing for the default player)
+/// before the subcommand.
+fn position_and_length_args(target: &[&str], config: &PlayerctlConfig) -> Result<(Option<u64>, Option<u64>)> {
+    let mut args = target.to_vec();
+    args.extend(["metadata", "--format", "{{position}};-;{{mpris:length}}"]);
+    let output = run_args_with(&args, config)?;
+    let (position, length) = output.split_once(";-;").unwrap_or((&output, ""));
+    let parse = |value: &str| match value.trim() {
+        "" => Ok(None),
+        value => value.parse().map(Some),
+    };
+    Ok((parse(position)?, parse(length)?))
+}
+
+/// Run playerctl with the given arguments, passed as-is, and trim the
+/// output.
+fn run_args(args: &[&str]) -> Result<String> {
+    Ok(run_raw(args)?.trim().to_owned())
+}
+
+/// Run playerctl with the given arguments and `config`, and return its
+/// output lines, see [`output_lines`]. Not for `metadata` without
+/// `--format`, whose values may span several lines.
+fn run_lines_with(args: &[&str], config: &PlayerctlConfig) -> Result<Vec<String>> {
+    Ok(output_lines(&run_raw_with(args, config)?))
+}
+
+/// Split command output into lines, trimming each line and dropping blank
+/// ones.
+fn output_lines(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Run playerctl with the given arguments and return its untrimmed output.
+fn run_raw(args: &[&str]) -> Result<String> {
+    run_raw_with(args, &PlayerctlConfig::default())
+}
+
+/// Run playerctl with the given arguments and `config`, and trim the output.
+fn run_args_with(args: &[&str], config: &PlayerctlConfig) -> Result<String> {
+    Ok(run_raw_with(args, config)?.trim().to_owned())
+}
+
+/// Run playerctl with the given arguments and `config`, retrying failed
+/// commands as configured, and return the untrimmed output.
+fn run_raw_with(args: &[&str], config: &PlayerctlConfig) -> Result<String> {
+    let mut retries = config.retries;
+    loop {
+        match run_once(args, config) {
+            Err(e) if retries > 0 && is_retryable(&e) => {
+                #[cfg(feature = "log")]
+                log::debug!("retrying playerctl {args:?} after: {e}");
+                retries -= 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Whether a failed command may succeed when run again: only generic
+/// command failures and I/O errors other than a missing or forbidden binary.
+fn is_retryable(error: &PlayerctlError) -> bool {
+    match error {
+        PlayerctlError::CommandError(_) => true,
+        PlayerctlError::IoError(e) => !matches!(e.kind(), io::ErrorKind::NotFound | io::ErrorKind::PermissionDenied),
+        _ => false,
+    }
+}
+
+/// Run playerctl once with the given arguments and `config`, calling the
+/// configured command hooks around it, and return its untrimmed output.
+fn run_once(args: &[&str], config: &PlayerctlConfig) -> Result<String> {
+    if let Some(hook) = &config.before_command {
+        hook(args);
+    }
+    let start = Instant::now();
+    let result = execute(args, config);
+    if let Some(hook) = &config.after_command {
+        hook(args, &result, start.elapsed());
+    }
+    result
+}
+
+/// Spawn playerctl with the given arguments and `config`, and return its
+/// untrimmed output.
+fn execute(args: &[&str], config: &PlayerctlConfig) -> Result<String> {
+    let mut command = config.command(args);
+
+    #[cfg(test)]
+    if let Some(output) = tests::mock_run(&command) {
+        config.report_stderr(tests::take_mock_stderr().as_bytes());
+        return output;
+    }
+
+    #[cfg(feature = "log")]
+    log::debug!("running playerctl {args:?}");
+
+    let output = match config.timeout {
+        Some(timeout) => output_with_timeout(&mut command, timeout)?,
+        None => command.output()?,
+    };
+
+    #[cfg(feature = "log")]
+    log::trace!(
+        "playerctl {args:?} exited with {}, stdout: {:?}, stderr: {:?}",
+        output.status,
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    config.report_stderr(&output.stderr);
+
+    let result = if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else if is_no_player(&output.stderr) {
+        Err(PlayerctlError::NoPlayer)
+    } else if is_seek_unsupported(&output.stderr) {
+        Err(PlayerctlError::Unavailable("the player doesn't support seeking".to_owned()))
+    } else {
         Err(PlayerctlError::CommandError(format!(
             "Command failed with status {}: {}",
             output.status,
             String::from_utf8_lossy(&output.stderr)
         )))
+    };
+
+    #[cfg(feature = "log")]
+    if let Err(e) = &result {
+        log::warn!("playerctl {args:?} failed: {e}");
+    }
+
+    result
+}
+
+/// How often a command with a timeout is checked for having exited.
+const TIMEOUT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Run `command` like [`Command::output`], but kill it and fail with
+/// [`PlayerctlError::Timeout`] if it runs longer than `timeout`.
+fn output_with_timeout(command: &mut Command, timeout: Duration) -> Result<Output> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    // Read both pipes in the background, so a chatty child can't block on a
+    // full pipe while we wait for it.
+    let stdout = read_in_background(child.stdout.take());
+    let stderr = read_in_background(child.stderr.take());
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            child.kill()?;
+            child.wait()?;
+            return Err(PlayerctlError::Timeout(timeout));
+        }
+        thread::sleep(TIMEOUT_POLL_INTERVAL);
+    };
+    let join = |reader: thread::JoinHandle<io::Result<Vec<u8>>>| {
+        reader
+            .join()
+            .map_err(|_| PlayerctlError::Other("playerctl output reader panicked".to_owned()))?
+            .map_err(PlayerctlError::from)
+    };
+    Ok(Output {
+        status,
+        stdout: join(stdout)?,
+        stderr: join(stderr)?,
+    })
+}
+
+/// Read `pipe` to its end on a new thread.
+fn read_in_background(pipe: Option<impl Read + Send + 'static>) -> thread::JoinHandle<io::Result<Vec<u8>>> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            pipe.read_to_end(&mut buf)?;
+        }
+        Ok(buf)
+    })
+}
+
+/// Check whether playerctl's stderr reports that no player is available.
+fn is_no_player(stderr: &[u8]) -> bool {
+    let stderr = String::from_utf8_lossy(stderr);
+    stderr.contains("No players found") || stderr.contains("No player could handle this command")
+}
+
+/// Check whether playerctl's stderr reports that the player can't seek.
+fn is_seek_unsupported(stderr: &[u8]) -> bool {
+    String::from_utf8_lossy(stderr).to_lowercase().contains("cannot seek")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{cell::RefCell, collections::VecDeque};
+
+    /// A command run through the mock runner.
+    #[derive(Debug, Clone)]
+    pub(crate) struct MockCall {
+        pub(crate) program: String,
+        pub(crate) args: Vec<String>,
+        pub(crate) envs: Vec<(String, Option<String>)>,
+        pub(crate) current_dir: Option<String>,
+    }
+
+    thread_local! {
+        static ACTIVE: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+        static RESPONSES: RefCell<VecDeque<Result<String>>> = RefCell::default();
+        static CALLS: RefCell<Vec<MockCall>> = RefCell::default();
+        static STDERR: RefCell<String> = RefCell::default();
+    }
+
+    /// Mock runner: record the command and return the next queued response.
+    /// Commands are run for real if [`mock`] wasn't called in this test.
+    pub(super) fn mock_run(command: &Command) -> Option<Result<String>> {
+        if !ACTIVE.with(|active| active.get()) {
+            return None;
+        }
+        let lossy = |s: &std::ffi::OsStr| s.to_string_lossy().into_owned();
+        let call = MockCall {
+            program: lossy(command.get_program()),
+            args: command.get_args().map(lossy).collect(),
+            envs: command.get_envs().map(|(key, val)| (lossy(key), val.map(lossy))).collect(),
+            current_dir: command.get_current_dir().map(|dir| lossy(dir.as_os_str())),
+        };
+        CALLS.with(|calls| calls.borrow_mut().push(call));
+        let response = RESPONSES.with(|responses| responses.borrow_mut().pop_front());
+        Some(response.unwrap_or_else(|| Err(PlayerctlError::Other("no mock response queued".to_owned()))))
+    }
+
+    /// Queue responses for the mock runner and clear previously recorded calls.
+    pub(crate) fn mock(responses: impl IntoIterator<Item = Result<String>>) {
+        ACTIVE.with(|active| active.set(true));
+        RESPONSES.with(|queue| *queue.borrow_mut() = responses.into_iter().collect());
+        CALLS.with(|calls| calls.borrow_mut().clear());
+    }
+
+    /// Make the next mocked call print `stderr`.
+    pub(crate) fn mock_stderr(stderr: &str) {
+        STDERR.with(|queued| *queued.borrow_mut() = stderr.to_owned());
+    }
+
+    /// Take the stderr queued by [`mock_stderr`], if any.
+    pub(super) fn take_mock_stderr() -> String {
+        STDERR.with(|queued| queued.take())
+    }
+
+    pub(crate) fn ok(output: &str) -> Result<String> {
+        Ok(output.to_owned())
+    }
+
+    /// Arguments of all calls made since the last [`mock`].
+    pub(crate) fn calls() -> Vec<Vec<String>> {
+        mock_calls().into_iter().map(|call| call.args).collect()
+    }
+
+    /// All calls made since the last [`mock`].
+    pub(crate) fn mock_calls() -> Vec<MockCall> {
+        CALLS.with(|calls| calls.borrow().clone())
+    }
+
+    #[test]
+    fn using_daemon_detects_proxy() {
+        let players = |names: &[&str]| names.iter().map(|&name| name.to_owned()).collect::<Vec<_>>();
+        assert!(lists_daemon(&players(&["playerctld", "spotify", "firefox.instance123"])));
+        assert!(!lists_daemon(&players(&["spotify", "playerctld-like"])));
+        assert!(!lists_daemon(&[]));
+
+        mock([ok("playerctld\nmpv\n"), Err(PlayerctlError::NoPlayer)]);
+        assert!(Playerctl::using_daemon().unwrap());
+        assert!(!Playerctl::using_daemon().unwrap());
+    }
+
+    #[test]
+    fn shutdown_daemon_waits_for_exit() {
+        mock([ok("playerctld\nmpv\n"), ok(""), ok("playerctld\nmpv\n"), ok("mpv\n")]);
+        Playerctl::shutdown_daemon().unwrap();
+        let calls = mock_calls();
+        assert_eq!(calls.len(), 4);
+        assert_eq!(calls[1].program, "playerctld");
+        assert_eq!(calls[1].args, ["shutdown"]);
+    }
+
+    #[test]
+    fn shutdown_daemon_without_daemon() {
+        mock([ok("mpv\n")]);
+        Playerctl::shutdown_daemon().unwrap();
+        assert_eq!(calls(), [["-l"]]);
+
+        mock([Err(PlayerctlError::NoPlayer)]);
+        Playerctl::shutdown_daemon().unwrap();
+
+        mock([ok("playerctld\n"), Err(PlayerctlError::CommandError("gone".to_owned())), ok("")]);
+        Playerctl::shutdown_daemon().unwrap();
+    }
+
+    #[test]
+    fn output_lines_trims_and_skips_blank_lines() {
+        assert_eq!(output_lines("mpv\nfirefox\n"), ["mpv", "firefox"]);
+        assert_eq!(output_lines("  mpv \r\n\n\t\nspotify"), ["mpv", "spotify"]);
+        assert!(output_lines("").is_empty());
+        assert!(output_lines("\n\n").is_empty());
+
+        mock([ok("mpv;-;1000\n\nvlc;-;2000\n"), ok("\nmpv\n\nvlc\n")]);
+        assert_eq!(Playerctl::get_position().unwrap(), HashMap::from([("mpv".to_owned(), 1000), ("vlc".to_owned(), 2000)]));
+        assert_eq!(Playerctl::list_players().unwrap(), ["mpv", "vlc"]);
+    }
+
+    #[test]
+    fn active_player_count_counts_listed_players() {
+        mock([ok("mpv\nfirefox.instance_1_23")]);
+        assert_eq!(Playerctl::active_player_count().unwrap(), 2);
+        assert_eq!(calls(), [["-l"]]);
+
+        mock([Err(PlayerctlError::NoPlayer)]);
+        assert_eq!(Playerctl::active_player_count().unwrap(), 0);
+
+        mock([ok("playerctld\nmpv\n")]);
+        assert_eq!(Playerctl::active_player_count().unwrap(), 1);
+    }
+
+    #[test]
+    fn artist_display_joins_artists() {
+        let display = |artist: Option<&str>| {
+            PlayerMetadata { xesam_artist: artist.map(str::to_owned), ..Default::default() }.artist_display()
+        };
+        assert_eq!(display(Some("A")).as_deref(), Some("A"));
+        assert_eq!(display(Some("A, B")).as_deref(), Some("A & B"));
+        assert_eq!(display(Some("A, B, C")).as_deref(), Some("A, B & C"));
+        assert_eq!(display(Some("")), None);
+        assert_eq!(display(None), None);
+    }
+
+    #[test]
+    fn primary_artist_falls_back_to_album_artist() {
+        let primary = |artist: Option<&str>, album_artist: Option<&str>| {
+            PlayerMetadata {
+                xesam_artist: artist.map(str::to_owned),
+                xesam_album_artist: album_artist.map(str::to_owned),
+                ..Default::default()
+            }
+            .primary_artist()
+        };
+        assert_eq!(primary(Some("A, B"), Some("C")).as_deref(), Some("A"));
+        assert_eq!(primary(None, Some("C, D")).as_deref(), Some("C"));
+        assert_eq!(primary(Some(""), Some("C")).as_deref(), Some("C"));
+        assert_eq!(primary(None, Some("")), None);
+        assert_eq!(primary(None, None), None);
+    }
+
+    #[test]
+    fn metadata_fast_parses_delimited_fields() {
+        mock([ok("mpv\u{1f}/1\u{1f}file:///a%20b.png\u{1f}1000\u{1f}Song\nfirefox\u{1f}\u{1f}\u{1f}\u{1f}Video\n")]);
+        let metadata = Playerctl::metadata_fast().unwrap();
+        assert_eq!(metadata.len(), 2);
+        let mpv = &metadata["mpv"];
+        assert_eq!(mpv.mpris_art_url.as_deref(), Some("file:///a b.png"));
+        assert_eq!(mpv.mpris_length, Some(1000));
+        assert_eq!(mpv.xesam_title.as_deref(), Some("Song"));
+        assert_eq!(metadata["firefox"].mpris_length, None);
+        assert_eq!(calls()[0][..3], ["-a", "metadata", "--format"]);
+    }
+
+    #[test]
+    fn skips_only_when_supported() {
+        let reply = |value| ok(&format!("method return serial=7\n   variant       boolean {value}\n"));
+        mock([ok("mpv\n"), reply("true"), ok(""), ok("firefox\n"), reply("false")]);
+        assert!(Playerctl::next_if_supported().unwrap());
+        assert!(!Playerctl::next_if_supported().unwrap());
+        let calls = mock_calls();
+        assert_eq!(calls.len(), 5);
+        assert_eq!(calls[1].program, "dbus-send");
+        assert!(calls[1].args.contains(&"--dest=org.mpris.MediaPlayer2.mpv".to_owned()));
+        assert_eq!(calls[1].args.last().unwrap(), "string:CanGoNext");
+        assert_eq!(calls[2].args, ["-p", "mpv", "next"]);
+        assert!(calls[4].args.contains(&"--dest=org.mpris.MediaPlayer2.firefox".to_owned()));
+
+        let unknown = Err(PlayerctlError::CommandError("UnknownProperty".to_owned()));
+        mock([ok("mpv\n"), reply("true"), ok(""), ok("mpv\n"), unknown]);
+        assert!(Playerctl::previous_if_supported().unwrap());
+        assert!(!Playerctl::previous_if_supported().unwrap());
+        let calls = mock_calls();
+        assert_eq!(calls[1].args.last().unwrap(), "string:CanGoPrevious");
+        assert_eq!(calls[2].args, ["-p", "mpv", "previous"]);
+        assert_eq!(calls.len(), 5);
+    }
+
+    #[test]
+    fn seek_supported_reads_can_seek() {
+        let reply = |value| format!("method return time=1.5 sender=:1.42 serial=7\n   variant       boolean {value}\n");
+        let unknown = "Error org.freedesktop.DBus.Error.UnknownProperty: Unknown property CanSeek";
+        let players = || ok("playerctld\nmpv\nfirefox\nchromium.instance123\n");
+        mock([
+            players(),
+            ok(&reply("true")),
+            players(),
+            ok(&reply("false")),
+            players(),
+            Err(PlayerctlError::CommandError(unknown.to_owned())),
+        ]);
+        assert!(Playerctl::seek_supported("mpv").unwrap());
+        assert!(!Playerctl::seek_supported("firefox").unwrap());
+        assert!(!Playerctl::seek_supported("chromium").unwrap());
+        let calls = mock_calls();
+        assert_eq!(calls[0].args, ["-l"]);
+        assert_eq!(calls[1].program, "dbus-send");
+        assert!(calls[5].args.contains(&"--dest=org.mpris.MediaPlayer2.chromium.instance123".to_owned()));
+        assert_eq!(
+            calls[1].args,
+            [
+                "--session",
+                "--print-reply",
+                "--dest=org.mpris.MediaPlayer2.mpv",
+                "/org/mpris/MediaPlayer2",
+                "org.freedesktop.DBus.Properties.Get",
+                "string:org.mpris.MediaPlayer2.Player",
+                "string:CanSeek"
+            ]
+        );
+
+        let service_unknown = "Error org.freedesktop.DBus.Error.ServiceUnknown: The name was not provided";
+        mock([
+            ok("mpv\n"),
+            ok("method return\n"),
+            ok("mpv\n"),
+            Err(PlayerctlError::CommandError(service_unknown.to_owned())),
+            ok("mpv\n"),
+            Err(PlayerctlError::CommandError("AccessDenied".to_owned())),
+            ok("mpv\n"),
+        ]);
+        assert!(matches!(Playerctl::seek_supported("mpv"), Err(PlayerctlError::Other(_))));
+        assert!(matches!(Playerctl::seek_supported("mpv"), Err(PlayerctlError::NoPlayer)));
+        assert!(matches!(Playerctl::seek_supported("mpv"), Err(PlayerctlError::CommandError(_))));
+        assert!(matches!(Playerctl::seek_supported("mp"), Err(PlayerctlError::NoPlayer)));
+        assert_eq!(mock_calls().len(), 7);
+    }
+
+    #[test]
+    fn play_pause_verified_retries_ignored_command() {
+        mock([ok("Paused"), ok(""), ok("Paused"), ok(""), ok("Playing")]);
+        assert_eq!(Playerctl::play_pause_verified(Duration::ZERO).unwrap(), TrackStatus::Playing);
+        assert_eq!(calls(), [["status"], ["play-pause"], ["status"], ["play-pause"], ["status"]]);
+
+        mock([ok("Paused"), ok(""), ok("Paused"), ok(""), ok("Paused")]);
+        assert!(matches!(
+            Playerctl::play_pause_verified(Duration::ZERO),
+            Err(PlayerctlError::Unavailable(_))
+        ));
+        assert_eq!(calls().len(), 5);
+    }
+
+    #[test]
+    fn play_pause_all_keeps_players_in_sync() {
+        use TrackStatus::*;
+        assert_eq!(play_pause_all_command(&[Playing, Paused]), "pause");
+        assert_eq!(play_pause_all_command(&[Paused, Stopped, Playing]), "pause");
+        assert_eq!(play_pause_all_command(&[Paused, Stopped]), "play");
+        assert_eq!(play_pause_all_command(&[]), "play");
+
+        mock([ok("Paused\nPlaying\n"), ok("")]);
+        Playerctl::play_pause_all().unwrap();
+        assert_eq!(calls(), [["-a", "status"], ["-a", "pause"]]);
+    }
+
+    #[test]
+    fn next_and_metadata_waits_for_track_change() {
+        mock([ok("mpv mpris:trackid /1\n"), ok(""), ok("mpv mpris:trackid /1\n"), ok("mpv mpris:trackid /2\n")]);
+        let metadata = Playerctl::next_and_metadata().unwrap().unwrap();
+        assert_eq!(metadata.mpris_trackid.as_deref(), Some("/2"));
+        assert_eq!(calls(), [vec!["metadata"], vec!["next"], vec!["metadata"], vec!["metadata"]]);
+
+        mock([Err(PlayerctlError::NoPlayer)]);
+        assert_eq!(Playerctl::previous_and_metadata().unwrap(), None);
+        assert_eq!(calls(), [["metadata"]]);
+    }
+
+    #[test]
+    fn list_players_with_status_keeps_order() {
+        mock([ok("spotify;Playing\nmpv;Paused\nfirefox.instance_1_23;Stopped\n")]);
+        assert_eq!(
+            Playerctl::list_players_with_status().unwrap(),
+            [
+                ("spotify".to_owned(), TrackStatus::Playing),
+                ("mpv".to_owned(), TrackStatus::Paused),
+                ("firefox.instance_1_23".to_owned(), TrackStatus::Stopped)
+            ]
+        );
+        assert_eq!(calls(), [["status", "-a", "-f", "{{playerName}};{{status}}"]]);
+
+        mock([Err(PlayerctlError::NoPlayer)]);
+        assert!(Playerctl::list_players_with_status().unwrap().is_empty());
+    }
+
+    #[test]
+    fn set_position_for_targets_player() {
+        mock([ok("")]);
+        Playerctl::set_position_for("mpv", 12.5).unwrap();
+        assert_eq!(calls(), [["-p", "mpv", "position", "12.5"]]);
+        assert!(matches!(
+            Playerctl::set_position_for("mpv", -1.),
+            Err(PlayerctlError::InvalidArgument(_))
+        ));
+        assert!(is_seek_unsupported(b"Player cannot seek\n"));
+        assert!(!is_seek_unsupported(b"No players found\n"));
+    }
+
+    #[test]
+    fn volume_for_targets_player() {
+        mock([ok("0.4\n"), ok(""), ok(""), ok("")]);
+        assert_eq!(Playerctl::get_volume_for("firefox").unwrap(), 0.4);
+        Playerctl::set_volume_for("firefox", 1.5).unwrap();
+        Playerctl::adjust_volume_for("firefox", -0.1).unwrap();
+        Playerctl::adjust_volume_for("firefox", 0.25).unwrap();
+        assert_eq!(
+            calls(),
+            [
+                vec!["-p", "firefox", "volume"],
+                vec!["-p", "firefox", "volume", "1"],
+                vec!["-p", "firefox", "volume", "0.1-"],
+                vec!["-p", "firefox", "volume", "0.25+"]
+            ]
+        );
+        assert!(matches!(
+            Playerctl::set_volume_for("firefox", f64::NAN),
+            Err(PlayerctlError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn help_commands_lists_available_commands() {
+        let help = "Usage:\n  playerctl [OPTION…] COMMAND - Controller for media players\n\n\
+            Application Options:\n  -p, --player=NAME   A comma separated list of names\n\n\
+            Available Commands:\n  play                    Command the player to play\n  \
+            position [OFFSET][+/-]  Command the player to go to the position\n  \
+            loop [STATUS]           Print or set the loop status\n\n\
+            Mostly compatible with the playerctl CLI.\n";
+        assert_eq!(help_commands(help), ["play", "position", "loop"]);
+        assert!(help_commands("Usage: playerctl").is_empty());
+    }
+
+    #[test]
+    fn metadata_for_many_reports_errors_per_player() {
+        mock([ok("mpv xesam:title Song\n"), Err(PlayerctlError::CommandError("failed".to_owned()))]);
+        let metadata = Playerctl::metadata_for_many(&["mpv", "vlc"], 1);
+        assert_eq!(metadata["mpv"].as_ref().unwrap().xesam_title.as_deref(), Some("Song"));
+        assert!(matches!(metadata["vlc"], Err(PlayerctlError::CommandError(_))));
+        assert_eq!(calls(), [["-p", "mpv", "metadata"], ["-p", "vlc", "metadata"]]);
+    }
+
+    #[test]
+    fn fetch_each_bounds_parallelism() {
+        let running = AtomicUsize::new(0);
+        let max_running = AtomicUsize::new(0);
+        let names = ["a", "b", "c", "d", "e", "f"];
+        let results = fetch_each(&names, 2, |name| {
+            let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+            max_running.fetch_max(now, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(20));
+            running.fetch_sub(1, Ordering::SeqCst);
+            name.to_uppercase()
+        });
+        assert_eq!(results.len(), names.len());
+        assert_eq!(results["c"], "C");
+        assert!(max_running.load(Ordering::SeqCst) <= 2);
+    }
+
+    #[test]
+    fn fetch_each_runs_in_parallel() {
+        // Each fetch waits for the other, so this only returns if both run
+        // at once.
+        let barrier = std::sync::Barrier::new(2);
+        let results = fetch_each(&["a", "b"], 2, |name| {
+            barrier.wait();
+            name.to_uppercase()
+        });
+        assert_eq!(results["a"], "A");
+        assert_eq!(results["b"], "B");
+    }
+
+    #[test]
+    fn first_available_metadata_falls_back_by_priority() {
+        // spotify isn't running, so playerctl picks the `%any` match.
+        mock([ok("mpv xesam:title Song\n")]);
+        let metadata = Playerctl::first_available_metadata(&["spotify", "%any"]).unwrap().unwrap();
+        assert_eq!(metadata.xesam_title.as_deref(), Some("Song"));
+        assert_eq!(calls(), [["-p", "spotify,%any", "metadata"]]);
+
+        mock([Err(PlayerctlError::NoPlayer)]);
+        assert_eq!(Playerctl::first_available_metadata(&["spotify"]).unwrap(), None);
+        assert!(matches!(
+            Playerctl::first_available_metadata(&["a,b"]),
+            Err(PlayerctlError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn metadata_with_warnings_skips_unparseable_values() {
+        let output = "mpv xesam:title Song\nmpv mpris:length long\n";
+        mock([ok(output), ok(output)]);
+        assert!(matches!(Playerctl::metadata(), Err(PlayerctlError::ParseLengthError(_))));
+
+        let (metadata, warnings) = Playerctl::metadata_with_warnings().unwrap();
+        assert_eq!(metadata["mpv"].xesam_title.as_deref(), Some("Song"));
+        assert_eq!(metadata["mpv"].mpris_length, None);
+        assert_eq!(metadata["mpv"].raw["mpris:length"], "long");
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("mpv: mpris:length: Failed to parse track length"), "{}", warnings[0]);
+    }
+
+    #[test]
+    fn metadata_snapshot_is_timestamped() {
+        mock([ok("mpv xesam:title Song\n")]);
+        let before = Instant::now();
+        let snapshot = Playerctl::metadata_snapshot().unwrap();
+        assert!(snapshot.fetched_at >= before && snapshot.fetched_at <= Instant::now());
+        assert!(snapshot.age() < Duration::from_secs(60));
+        assert_eq!(snapshot.players["mpv"].xesam_title.as_deref(), Some("Song"));
+    }
+
+    #[test]
+    fn metadata_with_options_skips_fields() {
+        let output = "mpv xesam:title Song\nmpv xesam:artist Artist\nmpv mpris:length 1000\nmpv xesam:genre Rock\n";
+        mock([ok(output), ok(output), ok(output)]);
+        let mut options = MetadataOptions { fields: Some(vec!["xesam:title".to_owned()]), ..Default::default() };
+        let metadata = &Playerctl::metadata_with_options(&options).unwrap()["mpv"];
+        assert_eq!(metadata.xesam_title.as_deref(), Some("Song"));
+        assert_eq!(metadata.xesam_artist, None);
+        assert_eq!(metadata.mpris_length, None);
+        assert_eq!(metadata.raw.len(), 4);
+
+        options.keep_raw = false;
+        let metadata = &Playerctl::metadata_with_options(&options).unwrap()["mpv"];
+        assert_eq!(metadata.xesam_title.as_deref(), Some("Song"));
+        assert_eq!(metadata.raw.keys().collect::<Vec<_>>(), ["xesam:title"]);
+
+        let all = Playerctl::metadata_with_options(&MetadataOptions::default()).unwrap();
+        assert_eq!(all, parse_metadata(output).unwrap());
+    }
+
+    #[test]
+    fn metadata_keeps_empty_trailing_field() {
+        mock([ok("mpv mpris:length     1000\nmpv xesam:artist     Artist\nmpv xesam:title      \n\n")]);
+        let metadata = Playerctl::metadata().unwrap();
+        let mpv = &metadata["mpv"];
+        assert_eq!(mpv.mpris_length, Some(1000));
+        assert_eq!(mpv.xesam_artist.as_deref(), Some("Artist"));
+        assert_eq!(mpv.xesam_title, None);
+        assert_eq!(mpv.raw["xesam:title"], "");
+        assert_eq!(calls(), [["metadata", "-a"]]);
+    }
+
+    #[test]
+    fn metadata_keeps_art_url_encoded_in_raw() {
+        mock([ok("mpv mpris:artUrl     file:///home/user/My%20Cover.jpg\n")]);
+        let metadata = Playerctl::metadata().unwrap();
+        let mpv = &metadata["mpv"];
+        assert_eq!(mpv.mpris_art_url.as_deref(), Some("file:///home/user/My Cover.jpg"));
+        assert_eq!(mpv.art_url_raw(), Some("file:///home/user/My%20Cover.jpg"));
+    }
+
+    #[test]
+    fn metadata_joins_multi_line_values() {
+        mock([ok("mpv xesam:comment    First line\nsecond line\n\nlast: line\nmpv xesam:title      Song\n")]);
+        let metadata = Playerctl::metadata().unwrap();
+        assert_eq!(metadata.len(), 1);
+        let mpv = &metadata["mpv"];
+        assert_eq!(mpv.raw["xesam:comment"], "First line\nsecond line\n\nlast: line");
+        assert_eq!(mpv.xesam_title.as_deref(), Some("Song"));
+        assert_eq!(mpv.raw.len(), 2);
+    }
+
+    #[test]
+    fn metadata_as_map_of_maps_decodes_urls() {
+        mock([ok(
+            "mpv xesam:url   file:///a%20b.opus\nmpv xesam:title A%20B\nfirefox mpris:length 862000000\n",
+        )]);
+        let metadata = Playerctl::metadata_as_map_of_maps().unwrap();
+        assert_eq!(metadata.len(), 2);
+        assert_eq!(metadata["mpv"]["xesam:url"], "file:///a b.opus");
+        assert_eq!(metadata["mpv"]["xesam:title"], "A%20B");
+        assert_eq!(metadata["firefox"]["mpris:length"], "862000000");
+    }
+
+    #[test]
+    fn get_position_parses_lines_with_trailing_newlines() {
+        mock([ok("mpv;-;1500000\nfirefox;-;0\n\n")]);
+        let positions = Playerctl::get_position().unwrap();
+        assert_eq!(positions.len(), 2);
+        assert_eq!(positions["mpv"], 1_500_000);
+        assert_eq!(positions["firefox"], 0);
+    }
+
+    #[test]
+    fn volume_is_clamped() {
+        mock([ok(""), ok(""), ok("0.250000\n")]);
+        Playerctl::set_volume(1.5).unwrap();
+        Playerctl::set_volume(-0.5).unwrap();
+        assert_eq!(Playerctl::get_volume().unwrap(), 0.25);
+        assert_eq!(calls(), [vec!["volume", "1"], vec!["volume", "0"], vec!["volume"]]);
+        assert!(matches!(Playerctl::set_volume(f64::NAN), Err(PlayerctlError::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn perceptual_volume_round_trips() {
+        for level in [0., 0.1, 0.5, 0.8, 1.] {
+            mock([ok("")]);
+            Playerctl::set_volume_perceptual(level).unwrap();
+            let volume = calls()[0][1].clone();
+            assert_eq!(volume.parse::<f64>().unwrap(), level * level);
+            mock([ok(&volume)]);
+            assert!((Playerctl::get_volume_perceptual().unwrap() - level).abs() < 1e-9);
+        }
+
+        mock([ok(""), ok("0.125\n")]);
+        Playerctl::set_volume_curve(0.5, 3.).unwrap();
+        assert_eq!(calls()[0], ["volume", "0.125"]);
+        assert!((Playerctl::get_volume_curve(3.).unwrap() - 0.5).abs() < 1e-9);
+        assert!(matches!(Playerctl::set_volume_curve(0.5, 0.), Err(PlayerctlError::InvalidArgument(_))));
+        assert!(matches!(Playerctl::set_volume_curve(f64::NAN, 2.), Err(PlayerctlError::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn loop_status_round_trips() {
+        for status in [LoopStatus::None, LoopStatus::Track, LoopStatus::Playlist] {
+            assert_eq!(status.to_string().parse::<LoopStatus>().unwrap(), status);
+        }
+        assert!("Forever".parse::<LoopStatus>().is_err());
+    }
+
+    #[test]
+    fn get_loop_and_shuffle() {
+        mock([ok("Track\n"), ok("On\n")]);
+        assert_eq!(Playerctl::get_loop().unwrap(), LoopStatus::Track);
+        assert!(Playerctl::get_shuffle().unwrap());
+        assert_eq!(calls(), [["loop"], ["shuffle"]]);
+    }
+
+    #[test]
+    fn loop_for_targets_player() {
+        mock([ok("Playlist\n"), ok("")]);
+        assert_eq!(Playerctl::get_loop_for("mpv").unwrap(), LoopStatus::Playlist);
+        Playerctl::set_loop_for("mpv", LoopStatus::None).unwrap();
+        assert_eq!(calls(), [vec!["-p", "mpv", "loop"], vec!["-p", "mpv", "loop", "None"]]);
+    }
+
+    #[test]
+    fn loop_for_reports_unsupported_players() {
+        mock([Err(PlayerctlError::NoPlayer), ok("firefox\n")]);
+        assert!(matches!(Playerctl::get_loop_for("firefox"), Err(PlayerctlError::Unavailable(_))));
+        mock([Err(PlayerctlError::NoPlayer), ok("mpv\n")]);
+        assert!(matches!(
+            Playerctl::set_loop_for("firefox", LoopStatus::Track),
+            Err(PlayerctlError::NoPlayer)
+        ));
+    }
+
+    #[test]
+    fn shuffle_for_targets_player() {
+        mock([ok("Off\n"), ok(""), ok("On\n"), ok("")]);
+        assert!(!Playerctl::get_shuffle_for("mpv").unwrap());
+        Playerctl::set_shuffle_for("mpv", true).unwrap();
+        assert!(!Playerctl::toggle_shuffle_for("mpv").unwrap());
+        assert_eq!(
+            calls(),
+            [
+                vec!["-p", "mpv", "shuffle"],
+                vec!["-p", "mpv", "shuffle", "On"],
+                vec!["-p", "mpv", "shuffle"],
+                vec!["-p", "mpv", "shuffle", "Off"],
+            ]
+        );
+
+        mock([Err(PlayerctlError::NoPlayer), ok("firefox\n")]);
+        assert!(matches!(Playerctl::get_shuffle_for("firefox"), Err(PlayerctlError::Unavailable(_))));
+    }
+
+    #[test]
+    fn set_loop_and_shuffle_for_all_players() {
+        mock([ok(""), ok("")]);
+        Playerctl::set_loop_all(LoopStatus::Playlist).unwrap();
+        Playerctl::set_shuffle_all(false).unwrap();
+        assert_eq!(calls(), [["-a", "loop", "Playlist"], ["-a", "shuffle", "Off"]]);
+    }
+
+    #[test]
+    fn status_or_none_reports_status() {
+        mock([ok("Paused\n")]);
+        assert_eq!(Playerctl::status_or_none().unwrap(), Some(TrackStatus::Paused));
+    }
+
+    #[test]
+    fn status_or_none_without_player() {
+        mock([Err(PlayerctlError::NoPlayer)]);
+        assert_eq!(Playerctl::status_or_none().unwrap(), None);
+    }
+
+    #[test]
+    fn is_playing_and_is_paused() {
+        for (status, playing, paused) in [("Playing", true, false), ("Paused", false, true), ("Stopped", false, false)] {
+            mock([ok(status), ok(status)]);
+            assert_eq!(Playerctl::is_playing().unwrap(), playing, "{status}");
+            assert_eq!(Playerctl::is_paused().unwrap(), paused, "{status}");
+        }
+        mock([Err(PlayerctlError::NoPlayer), Err(PlayerctlError::NoPlayer)]);
+        assert!(!Playerctl::is_playing().unwrap());
+        assert!(!Playerctl::is_paused().unwrap());
+    }
+
+    #[test]
+    fn ensure_playing_only_plays_when_needed() {
+        mock([ok("Playing")]);
+        Playerctl::ensure_playing().unwrap();
+        assert_eq!(calls(), [["status"]]);
+
+        for status in ["Paused", "Stopped"] {
+            mock([ok(status), ok("")]);
+            Playerctl::ensure_playing().unwrap();
+            assert_eq!(calls(), [["status"], ["play"]]);
+        }
+    }
+
+    #[test]
+    fn ensure_paused_only_pauses_when_playing() {
+        mock([ok("Playing"), ok("")]);
+        Playerctl::ensure_paused().unwrap();
+        assert_eq!(calls(), [["status"], ["pause"]]);
+
+        for status in ["Paused", "Stopped"] {
+            mock([ok(status)]);
+            Playerctl::ensure_paused().unwrap();
+            assert_eq!(calls(), [["status"]]);
+        }
+    }
+
+    #[test]
+    fn ensure_fails_without_player() {
+        mock([Err(PlayerctlError::NoPlayer)]);
+        assert!(matches!(Playerctl::ensure_playing(), Err(PlayerctlError::NoPlayer)));
+        mock([Err(PlayerctlError::NoPlayer)]);
+        assert!(matches!(Playerctl::ensure_paused(), Err(PlayerctlError::NoPlayer)));
+    }
+
+    #[test]
+    fn parses_versions() {
+        let version = |major, minor, patch| Version { major, minor, patch };
+        assert_eq!("v2.4.1".parse::<Version>().unwrap(), version(2, 4, 1));
+        assert_eq!("2.4.1\n".parse::<Version>().unwrap(), version(2, 4, 1));
+        assert_eq!("v2.0".parse::<Version>().unwrap(), version(2, 0, 0));
+        assert_eq!("v2.4.1-12-gabcdef".parse::<Version>().unwrap(), version(2, 4, 1));
+        for invalid in ["", "v2", "two.four", "2.4.1.1", "v2.x.1"] {
+            assert!(invalid.parse::<Version>().is_err(), "{invalid:?}");
+        }
+    }
+
+    #[test]
+    fn version_parsed_runs_version_flag() {
+        mock([ok("v2.4.1\n")]);
+        assert_eq!(Playerctl::version_parsed().unwrap().to_string(), "2.4.1");
+        assert_eq!(calls(), [["--version"]]);
+    }
+
+    #[test]
+    fn with_paused_pauses_and_resumes() {
+        mock([ok("Playing"), ok(""), ok("")]);
+        assert_eq!(Playerctl::with_paused(|| 42).unwrap(), 42);
+        assert_eq!(calls(), [["status"], ["pause"], ["play"]]);
+    }
+
+    #[test]
+    fn with_paused_leaves_paused_player_alone() {
+        mock([ok("Paused")]);
+        Playerctl::with_paused(|| ()).unwrap();
+        assert_eq!(calls(), [["status"]]);
+
+        mock([Err(PlayerctlError::NoPlayer)]);
+        Playerctl::with_paused(|| ()).unwrap();
+        assert_eq!(calls(), [["status"]]);
+    }
+
+    #[test]
+    fn with_paused_resumes_on_panic() {
+        mock([ok("Playing"), ok(""), ok("")]);
+        let result = std::panic::catch_unwind(|| Playerctl::with_paused(|| panic!("closure panicked")));
+        assert!(result.is_err());
+        assert_eq!(calls(), [["status"], ["pause"], ["play"]]);
+    }
+
+    #[test]
+    fn set_position_rejects_negative() {
+        assert!(matches!(Playerctl::set_position(-1.), Err(PlayerctlError::InvalidArgument(_))));
+        mock([ok("")]);
+        Playerctl::set_position(12.5).unwrap();
+        assert_eq!(calls(), [["position", "12.5"]]);
+    }
+
+    #[test]
+    fn smart_previous_restarts_track() {
+        mock([ok("42.000000"), ok("")]);
+        Playerctl::smart_previous(SMART_PREVIOUS_THRESHOLD).unwrap();
+        assert_eq!(calls(), [vec!["position"], vec!["position", "0"]]);
+    }
+
+    #[test]
+    fn smart_previous_near_start_goes_back() {
+        mock([ok("1.5"), ok("")]);
+        Playerctl::smart_previous(SMART_PREVIOUS_THRESHOLD).unwrap();
+        assert_eq!(calls(), [["position"], ["previous"]]);
+    }
+
+    #[test]
+    fn smart_previous_with_unknown_position() {
+        mock([Err(PlayerctlError::CommandError("no position".to_owned())), ok("")]);
+        Playerctl::smart_previous(SMART_PREVIOUS_THRESHOLD).unwrap();
+        assert_eq!(calls(), [["position"], ["previous"]]);
+    }
+
+    #[test]
+    fn play_uri_opens_then_plays() {
+        mock([ok(""), ok(""), ok("Playing")]);
+        Playerctl::play_uri("/music/song.opus").unwrap();
+        assert_eq!(calls(), [vec!["open", "/music/song.opus"], vec!["play"], vec!["status"]]);
+    }
+
+    #[test]
+    fn play_uri_retries_until_playing() {
+        mock([ok(""), ok(""), ok("Stopped"), ok(""), ok("Playing")]);
+        Playerctl::play_uri("/music/song.opus").unwrap();
+        assert_eq!(calls()[1..], [["play"], ["status"], ["play"], ["status"]]);
+    }
+
+    #[test]
+    fn open_normalizes_local_paths() {
+        // Tests run in the crate root, so this path is relative to it.
+        let dir = PathBuf::from(format!("target/playerctl-open-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let relative = dir.join("my song.opus");
+        fs::write(&relative, b"").unwrap();
+        let absolute = fs::canonicalize(&relative).unwrap();
+        let url = file_url(absolute.to_str().unwrap());
+        assert!(url.starts_with("file:///") && url.ends_with("/my%20song.opus"), "{url}");
+
+        mock([ok(""), ok(""), ok(""), ok("")]);
+        Playerctl::open(relative.to_str().unwrap()).unwrap();
+        Playerctl::open(absolute.to_str().unwrap()).unwrap();
+        Playerctl::open("https://example.com/a%20b.mp3").unwrap();
+        Playerctl::open_with(relative.to_str().unwrap(), false).unwrap();
+        assert_eq!(
+            calls(),
+            [
+                vec!["open".to_owned(), url.clone()],
+                vec!["open".to_owned(), url],
+                vec!["open".to_owned(), "https://example.com/a%20b.mp3".to_owned()],
+                vec!["open".to_owned(), relative.to_str().unwrap().to_owned()],
+            ]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(normalize_uri("spotify:track:123", None), "spotify:track:123");
+        assert_eq!(normalize_uri("missing/song.opus", None), "missing/song.opus");
+    }
+
+    #[test]
+    fn open_many_opens_in_order() {
+        mock([ok(""), ok("")]);
+        Playerctl::open_many(&["/music/a b.opus", "https://example.com/c"]).unwrap();
+        assert_eq!(calls(), [["open", "/music/a b.opus"], ["open", "https://example.com/c"]]);
+    }
+
+    #[test]
+    fn open_many_stops_at_first_failure() {
+        mock([ok(""), Err(PlayerctlError::CommandError("failed".to_owned()))]);
+        let err = Playerctl::open_many(&["a", "b", "c"]).unwrap_err();
+        assert!(matches!(err, PlayerctlError::OpenFailed { opened: 1, total: 3, .. }));
+        assert_eq!(calls().len(), 2);
+    }
+
+    #[test]
+    fn chapter_skip_forward_seeks_by_chapter() {
+        mock([ok("60000000;-;300000000"), ok("")]);
+        Playerctl::chapter_skip_forward(Duration::from_secs(90)).unwrap();
+        assert_eq!(calls(), [vec!["metadata", "--format", "{{position}};-;{{mpris:length}}"], vec!["position", "150"]]);
+    }
+
+    #[test]
+    fn chapter_skips_clamp_to_track() {
+        mock([ok("290000000;-;300000000"), ok("")]);
+        Playerctl::chapter_skip_forward(Duration::from_secs(60)).unwrap();
+        assert_eq!(calls()[1], ["position", "300"]);
+
+        mock([ok("30000000;-;300000000"), ok("")]);
+        Playerctl::chapter_skip_backward(Duration::from_secs(60)).unwrap();
+        assert_eq!(calls()[1], ["position", "0"]);
+    }
+
+    #[test]
+    fn chapter_skip_without_length() {
+        mock([ok("30000000;-;")]);
+        assert!(matches!(
+            Playerctl::chapter_skip_forward(Duration::from_secs(60)),
+            Err(PlayerctlError::Unavailable(_))
+        ));
+    }
+
+    #[test]
+    fn relative_offsets() {
+        assert_eq!(parse_relative_offset("+1:30").unwrap(), 90.);
+        assert_eq!(parse_relative_offset("-0:45").unwrap(), -45.);
+        assert_eq!(parse_relative_offset("+10").unwrap(), 10.);
+        assert_eq!(parse_relative_offset("-1:00:05").unwrap(), -3605.);
+        assert_eq!(parse_relative_offset("+0:02.5").unwrap(), 2.5);
+    }
+
+    #[test]
+    fn malformed_relative_offsets() {
+        for offset in ["1:30", "", "+", "+1:", "+:30", "+1:75", "+a:30", "+1.5:30", "+-5", "+1:2:3:4"] {
+            assert!(
+                matches!(parse_relative_offset(offset), Err(PlayerctlError::InvalidArgument(_))),
+                "{offset:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn seek_relative_str_dispatches_to_position() {
+        mock([ok("")]);
+        Playerctl::seek_relative_str("-1:30").unwrap();
+        assert_eq!(calls(), [["position", "90-"]]);
+    }
+
+    fn metadata_with_raw(entries: &[(&str, &str)]) -> PlayerMetadata {
+        PlayerMetadata {
+            raw: entries
+                .iter()
+                .map(|&(key, val)| (key.to_owned(), val.to_owned()))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn audio_properties_from_raw() {
+        let metadata = metadata_with_raw(&[("xesam:audioBitrate", "320000"), ("xesam:sampleRate", "44100")]);
+        assert_eq!(metadata.bitrate(), Some(320_000));
+        assert_eq!(metadata.sample_rate(), Some(44_100));
+        assert_eq!(metadata.get_f64("xesam:sampleRate"), Some(44_100.));
+    }
+
+    #[test]
+    fn stream_id_from_youtube_urls() {
+        let stream_id = |url: &str| PlayerMetadata { xesam_url: Some(url.to_owned()), ..Default::default() }.stream_id();
+        assert_eq!(stream_id("https://www.youtube.com/watch?v=I9nK9FggzIg").as_deref(), Some("I9nK9FggzIg"));
+        assert_eq!(stream_id("https://music.youtube.com/watch?list=PL1&v=a-b_c#t=3").as_deref(), Some("a-b_c"));
+        assert_eq!(stream_id("https://youtu.be/I9nK9FggzIg?t=42").as_deref(), Some("I9nK9FggzIg"));
+        assert_eq!(stream_id("https://www.youtube.com/feed/subscriptions"), None);
+        assert_eq!(stream_id("https://youtu.be/"), None);
+        assert_eq!(stream_id("https://notyoutube.com/watch?v=abc"), None);
+        assert_eq!(stream_id("file:///music/song.opus"), None);
+        assert_eq!(PlayerMetadata::default().stream_id(), None);
+    }
+
+    #[test]
+    fn decoded_percent_decodes_raw_values() {
+        let metadata = metadata_with_raw(&[
+            ("custom:url", "https://example.com/a%20b%C3%A9"),
+            ("xesam:title", "Plain title"),
+            ("custom:broken", "%FF%FE"),
+        ]);
+        assert_eq!(metadata.decoded("custom:url").as_deref(), Some("https://example.com/a bé"));
+        assert_eq!(metadata.decoded("xesam:title").as_deref(), Some("Plain title"));
+        assert_eq!(metadata.decoded("custom:broken"), None);
+        assert_eq!(metadata.decoded("custom:missing"), None);
+    }
+
+    #[test]
+    fn track_id_normalized_strips_quotes_and_prefixes() {
+        let normalized = |id: &str| {
+            PlayerMetadata { mpris_trackid: Some(id.to_owned()), ..Default::default() }.track_id_normalized()
+        };
+        assert_eq!(normalized("/63").as_deref(), Some("/63"));
+        assert_eq!(normalized("'/63'").as_deref(), Some("/63"));
+        assert_eq!(normalized("\"/63\"").as_deref(), Some("/63"));
+        assert_eq!(normalized("objectpath '/org/mpris/Track/1'").as_deref(), Some("/org/mpris/Track/1"));
+        assert_eq!(normalized("@o '/63' ").as_deref(), Some("/63"));
+        assert_eq!(normalized("spotify:track:4uLU6hMC").as_deref(), Some("spotify:track:4uLU6hMC"));
+        assert_eq!(normalized("''"), None);
+        assert_eq!(PlayerMetadata::default().track_id_normalized(), None);
+    }
+
+    #[test]
+    fn get_bool_accepts_known_forms() {
+        for (val, expected) in [
+            ("true", Some(true)),
+            ("TRUE", Some(true)),
+            ("1", Some(true)),
+            ("On", Some(true)),
+            ("false", Some(false)),
+            ("False", Some(false)),
+            ("0", Some(false)),
+            ("OFF", Some(false)),
+            ("yes", None),
+            ("", None),
+        ] {
+            let metadata = metadata_with_raw(&[("custom:flag", val)]);
+            assert_eq!(metadata.get_bool("custom:flag"), expected, "{val:?}");
+        }
+        assert_eq!(PlayerMetadata::default().get_bool("custom:flag"), None);
+    }
+
+    #[test]
+    fn audio_properties_ignore_invalid_values() {
+        let metadata = metadata_with_raw(&[("xesam:audioBitrate", "high"), ("xesam:bitrate", "128000")]);
+        assert_eq!(metadata.bitrate(), Some(128_000));
+        assert_eq!(metadata.sample_rate(), None);
+        assert_eq!(metadata_with_raw(&[("xesam:audioBitrate", "high")]).bitrate(), None);
+    }
+
+    #[test]
+    fn metadata_diff_identical() {
+        let metadata = metadata_with_raw(&[("xesam:title", "Song")]);
+        assert!(Playerctl::metadata_diff(&metadata, &metadata.clone()).is_empty());
+    }
+
+    #[test]
+    fn metadata_diff_added_removed_changed() {
+        let change = |field: &str, old: Option<&str>, new: Option<&str>| MetadataChange {
+            field: field.to_owned(),
+            old: old.map(str::to_owned),
+            new: new.map(str::to_owned),
+        };
+        let mut old = metadata_with_raw(&[("mpris:length", "1000"), ("xesam:title", "A"), ("xesam:album", "X")]);
+        old.mpris_length = Some(1000);
+        old.xesam_title = Some("A".to_owned());
+        old.xesam_album = Some("X".to_owned());
+        let mut new = metadata_with_raw(&[("mpris:length", "1000"), ("xesam:title", "B"), ("xesam:artist", "Y")]);
+        new.mpris_length = Some(1000);
+        new.xesam_title = Some("B".to_owned());
+        new.xesam_artist = Some("Y".to_owned());
+
+        assert_eq!(
+            Playerctl::metadata_diff(&old, &new),
+            [
+                change("xesam_title", Some("A"), Some("B")),
+                change("xesam_album", Some("X"), None),
+                change("xesam_artist", None, Some("Y")),
+                change("xesam:album", Some("X"), None),
+                change("xesam:artist", None, Some("Y")),
+                change("xesam:title", Some("A"), Some("B")),
+            ]
+        );
+    }
+
+    #[test]
+    fn art_bytes_reads_local_files() {
+        let path = std::env::temp_dir().join(format!("playerctl-art-{}.png", std::process::id()));
+        fs::write(&path, b"art").unwrap();
+        let metadata = PlayerMetadata {
+            mpris_art_url: Some(format!("file://{}", path.display())),
+            ..Default::default()
+        };
+        assert_eq!(metadata.art_bytes().unwrap().unwrap(), b"art");
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn background_and_extended_art_urls() {
+        let output = "spotify mpris:artUrl file:///art/cover%20small.jpg\n\
+                      spotify mpris:backgroundUrl file:///art/back%20ground.jpg\n\
+                      spotify xesam:largeArtUrl https://example.com/large%2Bcover.jpg\n\
+                      spotify xesam:url https://example.com/track\n";
+        mock([ok(output)]);
+        let metadata = &Playerctl::metadata().unwrap()["spotify"];
+        assert_eq!(metadata.art_path(), Some(PathBuf::from("/art/cover small.jpg")));
+        assert_eq!(metadata.background_url().as_deref(), Some("file:///art/back ground.jpg"));
+        assert_eq!(metadata.background_path(), Some(PathBuf::from("/art/back ground.jpg")));
+        assert_eq!(
+            metadata.art_urls(),
+            [
+                ("mpris:artUrl", "file:///art/cover small.jpg".to_owned()),
+                ("mpris:backgroundUrl", "file:///art/back ground.jpg".to_owned()),
+                ("xesam:largeArtUrl", "https://example.com/large+cover.jpg".to_owned()),
+            ]
+        );
+        assert_eq!(PlayerMetadata::default().background_url(), None);
+    }
+
+    #[test]
+    fn seek_start_and_end_positions() {
+        mock([ok(""), ok("1000000;-;180000000"), ok("")]);
+        Playerctl::seek_start().unwrap();
+        Playerctl::seek_end().unwrap();
+        assert_eq!(calls()[0], ["position", "0"]);
+        assert_eq!(calls()[2], ["position", "179.5"]);
+
+        mock([ok(""), ok(";-;300000"), ok("")]);
+        Playerctl::seek_start_for("mpv").unwrap();
+        Playerctl::seek_end_for("mpv").unwrap();
+        assert_eq!(calls()[0], ["-p", "mpv", "position", "0"]);
+        assert_eq!(calls()[1][..2], ["-p", "mpv"]);
+        assert_eq!(calls()[2], ["-p", "mpv", "position", "0"]);
+
+        mock([ok("1000000;-;")]);
+        assert!(matches!(Playerctl::seek_end(), Err(PlayerctlError::Unavailable(_))));
+    }
+
+    #[test]
+    fn current_player_handle_binds_resolved_name() {
+        mock([ok("firefox.instance_1_23\n"), ok(""), ok("")]);
+        let player = Playerctl::current_player_handle().unwrap();
+        assert_eq!(player.name(), "firefox.instance_1_23");
+        player.pause().unwrap();
+        player.next().unwrap();
+        assert_eq!(
+            calls(),
+            [
+                vec!["status", "-f", "{{playerInstance}}"],
+                vec!["-p", "firefox.instance_1_23", "pause"],
+                vec!["-p", "firefox.instance_1_23", "next"]
+            ]
+        );
+
+        mock([Err(PlayerctlError::NoPlayer)]);
+        assert!(matches!(Playerctl::current_player_handle(), Err(PlayerctlError::NoPlayer)));
+    }
+
+    #[test]
+    fn missing_length_is_unknown() {
+        let metadata = parse_metadata("firefox xesam:title Live\nfirefox mpris:length \nmpv mpris:length 0\n").unwrap();
+        assert_eq!(metadata["firefox"].length(), None);
+        assert!(!metadata["firefox"].has_length());
+        assert_eq!(metadata["mpv"].length(), Some(Duration::ZERO));
+        assert!(metadata["mpv"].has_length());
+    }
+
+    #[test]
+    fn length_accepts_unit_suffixes() {
+        assert_eq!(parse_length("160680000"), Ok(160680000));
+        assert_eq!(parse_length("123 us"), Ok(123));
+        assert_eq!(parse_length("123µs"), Ok(123));
+        assert_eq!(parse_length(" 42\t"), Ok(42));
+        assert!(parse_length("us").is_err());
+        assert!(parse_length("12x3").is_err());
+        assert!(parse_length("-5").is_err());
+    }
+
+    #[test]
+    fn metadata_current_requires_a_player() {
+        mock([ok("mpv\n"), ok("mpv xesam:title Song\n")]);
+        let metadata = Playerctl::metadata_current().unwrap();
+        assert_eq!(metadata.xesam_title.as_deref(), Some("Song"));
+        assert_eq!(calls()[1], ["-p", "mpv", "metadata"]);
+
+        mock([Err(PlayerctlError::NoPlayer)]);
+        assert!(matches!(Playerctl::metadata_current(), Err(PlayerctlError::NoPlayer)));
+
+        // The player exited between resolving its name and fetching metadata.
+        mock([ok("mpv\n"), Err(PlayerctlError::NoPlayer), Err(PlayerctlError::NoPlayer)]);
+        assert!(matches!(Playerctl::metadata_current(), Err(PlayerctlError::NoPlayer)));
+    }
+
+    #[test]
+    fn metadata_for_tells_missing_player_from_empty_metadata() {
+        mock([Err(PlayerctlError::NoPlayer), ok("firefox.instance_1_23\n")]);
+        assert!(matches!(Playerctl::metadata_for("mpv"), Err(PlayerctlError::NoPlayer)));
+        assert_eq!(calls(), [vec!["-p", "mpv", "metadata"], vec!["-l"]]);
+
+        mock([Err(PlayerctlError::NoPlayer), ok("mpv\n")]);
+        assert_eq!(Playerctl::metadata_for("mpv").unwrap(), PlayerMetadata::default());
+
+        mock([ok("")]);
+        assert_eq!(Playerctl::metadata_for("mpv").unwrap(), PlayerMetadata::default());
+
+        assert!(is_no_player(b"No players found\n"));
+        assert!(is_no_player(b"No player could handle this command\n"));
+        assert!(!is_no_player(b"Player cannot seek\n"));
+    }
+
+    #[test]
+    fn status_text_is_verbatim() {
+        mock([ok("Buffering\n")]);
+        assert_eq!(Playerctl::status_text().unwrap(), "Buffering");
+        assert_eq!(calls(), [["status"]]);
+    }
+
+    #[test]
+    fn duplicate_keys_are_kept_in_raw_multi() {
+        mock([ok("mpv xesam:title First\nmpv xesam:artist A\nmpv xesam:title Second\nmpv xesam:title Third\n")]);
+        let metadata = &Playerctl::metadata().unwrap()["mpv"];
+        assert_eq!(metadata.xesam_title.as_deref(), Some("Third"));
+        assert_eq!(metadata.raw["xesam:title"], "Third");
+        assert_eq!(metadata.raw_multi["xesam:title"], ["First", "Second", "Third"]);
+        assert!(!metadata.raw_multi.contains_key("xesam:artist"));
+    }
+
+    #[test]
+    fn now_playing_line_trims_and_falls_back() {
+        mock([ok("Artist - Title\n"), Err(PlayerctlError::NoPlayer), Err(PlayerctlError::NoPlayer)]);
+        assert_eq!(Playerctl::now_playing_line("{{artist}} - {{title}}").unwrap(), "Artist - Title");
+        assert_eq!(Playerctl::now_playing_line("{{title}}").unwrap(), "");
+        assert_eq!(Playerctl::now_playing_line_or("{{title}}", "-").unwrap(), "-");
+        assert_eq!(calls()[0], ["metadata", "--format", "{{artist}} - {{title}}"]);
+
+        mock([ok("Title\n")]);
+        let template = FormatTemplate::new().field("title");
+        assert_eq!(Playerctl::now_playing_line(&template).unwrap(), "Title");
+    }
+
+    #[test]
+    fn remaining_fraction_target_math() {
+        assert_eq!(remaining_fraction_target(20, 120, 0.1), 30);
+        assert_eq!(remaining_fraction_target(20, 120, 0.), 20);
+        assert_eq!(remaining_fraction_target(20, 120, 1.), 120);
+        assert_eq!(remaining_fraction_target(20, 120, 2.), 120);
+        assert_eq!(remaining_fraction_target(100, 120, -10.), 0);
+        assert_eq!(remaining_fraction_target(150, 120, 0.5), 120);
+
+        mock([ok("60000000;-;160000000"), ok("")]);
+        Playerctl::seek_by_remaining_fraction(0.5).unwrap();
+        assert_eq!(calls()[1], ["position", "110"]);
+        assert!(matches!(
+            Playerctl::seek_by_remaining_fraction(f32::NAN),
+            Err(PlayerctlError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn ping_treats_no_player_as_healthy() {
+        mock([ok("mpv\n"), Err(PlayerctlError::NoPlayer), Err(PlayerctlError::CommandError("no bus".to_owned()))]);
+        Playerctl::ping().unwrap();
+        Playerctl::ping().unwrap();
+        assert!(matches!(Playerctl::ping(), Err(PlayerctlError::CommandError(_))));
+        assert_eq!(calls(), [["-l"], ["-l"], ["-l"]]);
+    }
+
+    #[test]
+    fn decodes_html_entities() {
+        assert_eq!(decode_html_entities("Rock &amp; Roll"), "Rock & Roll");
+        assert_eq!(decode_html_entities("It&#39;s &#x1F600;"), "It's \u{1F600}");
+        assert_eq!(decode_html_entities("AT&T &nbsp; &#xZZ; &"), "AT&T &nbsp; &#xZZ; &");
+        assert_eq!(decode_html_entities("&amp;amp;"), "&amp;");
+    }
+
+    #[test]
+    fn seek_and_play_sequences() {
+        mock([ok(""), ok("Paused"), ok("")]);
+        Playerctl::seek_and_play(42.).unwrap();
+        assert_eq!(calls(), [vec!["position", "42"], vec!["status"], vec!["play"]]);
+
+        // A paused player that started playing on seek is paused again.
+        mock([ok("Paused"), ok(""), ok("Playing"), ok("")]);
+        Playerctl::seek_and_keep_state(10.).unwrap();
+        assert_eq!(calls(), [vec!["status"], vec!["position", "10"], vec!["status"], vec!["pause"]]);
+
+        mock([ok("Playing"), ok(""), ok("Playing")]);
+        Playerctl::seek_and_keep_state(10.).unwrap();
+        assert_eq!(calls().len(), 3);
+
+        mock([]);
+        assert!(matches!(Playerctl::seek_and_play(-1.), Err(PlayerctlError::InvalidArgument(_))));
+        assert!(matches!(Playerctl::seek_and_keep_state(-1.), Err(PlayerctlError::InvalidArgument(_))));
+        assert!(calls().is_empty());
+    }
+
+    #[test]
+    fn comments_keep_commas() {
+        let output = concat!(
+            "mpv xesam:comment Show notes\nline two, with a comma\n",
+            "vlc xesam:comment A, B\nvlc xesam:comment C\n",
+            "firefox xesam:comment \n",
+        );
+        mock([ok(output)]);
+        let metadata = Playerctl::metadata().unwrap();
+        assert_eq!(metadata["mpv"].comments(), Some(vec!["Show notes\nline two, with a comma".to_owned()]));
+        assert_eq!(metadata["vlc"].comments(), Some(vec!["A, B".to_owned(), "C".to_owned()]));
+        assert_eq!(metadata["firefox"].comments(), None);
+        assert_eq!(PlayerMetadata::default().comments(), None);
+    }
+
+    #[test]
+    fn format_all_splits_per_player() {
+        mock([ok("mpv\u{1f}Artist - Song\nfirefox\u{1f} - Video\nspotify\u{1f}\n")]);
+        let lines = Playerctl::format_all("{{artist}} - {{title}}").unwrap();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines["mpv"], "Artist - Song");
+        assert_eq!(lines["firefox"], " - Video");
+        assert_eq!(lines["spotify"], "");
+        assert_eq!(calls(), [["-a", "metadata", "--format", "{{playerName}}\u{1f}{{artist}} - {{title}}"]]);
+
+        mock([Err(PlayerctlError::NoPlayer)]);
+        assert!(Playerctl::format_all("{{title}}").unwrap().is_empty());
+    }
+
+    #[test]
+    fn wait_for_player_polls_until_one_appears() {
+        let poll = Duration::from_millis(1);
+        mock([ok(""), Err(PlayerctlError::NoPlayer), ok("mpv\n")]);
+        assert_eq!(Playerctl::wait_for_player(Duration::from_secs(10), poll).unwrap().as_deref(), Some("mpv"));
+        assert_eq!(calls().len(), 3);
+
+        mock([ok("playerctld\n"), ok("playerctld\nmpv\n")]);
+        assert_eq!(Playerctl::wait_for_player(Duration::from_secs(10), poll).unwrap().as_deref(), Some("mpv"));
+        assert_eq!(calls().len(), 2);
+
+        mock((0..100).map(|_| ok("")));
+        assert_eq!(Playerctl::wait_for_player(Duration::ZERO, poll).unwrap(), None);
+        assert_eq!(calls().len(), 1);
+    }
+
+    #[test]
+    fn auto_rating_is_clamped() {
+        let rating = |value: &str| {
+            let mut metadata = PlayerMetadata::default();
+            metadata.raw.insert("xesam:autoRating".to_owned(), value.to_owned());
+            metadata.auto_rating()
+        };
+        assert_eq!(rating("0.25"), Some(0.25));
+        assert_eq!(rating("5"), Some(1.));
+        assert_eq!(rating("-0.5"), Some(0.));
+        assert_eq!(rating("high"), None);
+        assert_eq!(rating("NaN"), None);
+        assert_eq!(PlayerMetadata::default().auto_rating(), None);
+    }
+
+    #[test]
+    fn seek_to_percent_clamps_fraction() {
+        mock([ok(";-;200000000"), ok(""), ok("5;-;200000000"), ok(""), ok("5;-;200000000"), ok("")]);
+        Playerctl::seek_to_percent(0.25).unwrap();
+        Playerctl::seek_to_percent_for("mpv", 1.5).unwrap();
+        Playerctl::seek_to_percent_for("mpv", -1.).unwrap();
+        let calls = calls();
+        assert_eq!(calls[1], ["position", "50"]);
+        assert_eq!(calls[2][..2], ["-p", "mpv"]);
+        assert_eq!(calls[3], ["-p", "mpv", "position", "200"]);
+        assert_eq!(calls[5], ["-p", "mpv", "position", "0"]);
+
+        mock([ok("5;-;")]);
+        assert!(matches!(Playerctl::seek_to_percent_for("mpv", 0.5), Err(PlayerctlError::Unavailable(_))));
+        assert!(matches!(
+            Playerctl::seek_to_percent_for("mpv", f32::NAN),
+            Err(PlayerctlError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn art_source_by_scheme() {
+        let art = |url: &str| {
+            PlayerMetadata {
+                mpris_art_url: Some(url.to_owned()),
+                ..Default::default()
+            }
+            .art()
+        };
+        assert_eq!(art("file:///music/a b.jpg"), Some(ArtSource::File("/music/a b.jpg".into())));
+        assert_eq!(
+            art("https://i.ytimg.com/vi/x/hq.jpg"),
+            Some(ArtSource::Remote("https://i.ytimg.com/vi/x/hq.jpg".to_owned()))
+        );
+        assert_eq!(art("cover.jpg"), None);
+        assert_eq!(PlayerMetadata::default().art(), None);
+    }
+
+    #[test]
+    fn art_bytes_ignores_remote_art() {
+        let metadata = PlayerMetadata {
+            mpris_art_url: Some("https://example.com/cover.jpg".to_owned()),
+            ..Default::default()
+        };
+        assert!(metadata.art_bytes().is_none());
+        assert!(PlayerMetadata::default().art_bytes().is_none());
+    }
+
+    #[test]
+    fn remaining_subtracts_position_from_length() {
+        mock([ok("1000000;-;5000000")]);
+        assert_eq!(Playerctl::remaining("mpv").unwrap(), Some(Duration::from_secs(4)));
+        assert_eq!(calls()[0][..2], ["-p", "mpv"]);
+    }
+
+    #[test]
+    fn remaining_clamps_to_zero() {
+        mock([ok("5000100;-;5000000")]);
+        assert_eq!(Playerctl::remaining("mpv").unwrap(), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn get_position_for_targets_player() {
+        mock([ok("1500000\n")]);
+        assert_eq!(Playerctl::get_position_for("mpv").unwrap(), Some(Duration::from_millis(1500)));
+        assert_eq!(calls(), [["-p", "mpv", "status", "-f", "{{position}}"]]);
+    }
+
+    #[test]
+    fn get_position_for_unavailable() {
+        mock([Err(PlayerctlError::NoPlayer)]);
+        assert_eq!(Playerctl::get_position_for("mpv").unwrap(), None);
+        mock([ok("")]);
+        assert_eq!(Playerctl::get_position_for("mpv").unwrap(), None);
+    }
+
+    #[test]
+    fn position_fraction_of_length() {
+        mock([ok("1000000;-;4000000")]);
+        assert_eq!(Playerctl::position_fraction("mpv").unwrap(), Some(0.25));
+        mock([ok("5000000;-;4000000")]);
+        assert_eq!(Playerctl::position_fraction("mpv").unwrap(), Some(1.));
+    }
+
+    #[test]
+    fn position_fraction_without_length() {
+        mock([ok("1000000;-;0")]);
+        assert_eq!(Playerctl::position_fraction("mpv").unwrap(), None);
+        mock([ok("1000000;-;")]);
+        assert_eq!(Playerctl::position_fraction("mpv").unwrap(), None);
+    }
+
+    #[test]
+    fn full_state_from_one_call() {
+        let line = [
+            "Playing",
+            "1500000",
+            "'/63'",
+            "4000000",
+            "file:///a%20b.png",
+            "",
+            "",
+            "X ;-; Y",
+            "",
+            "Artist",
+            "A ;-; B",
+        ];
+        mock([ok(&line.join("\u{1f}"))]);
+        let state = Playerctl::full_state("mpv").unwrap();
+        assert_eq!(calls().len(), 1);
+        assert_eq!(calls()[0][..4], ["-p", "mpv", "metadata", "--format"]);
+        assert!(calls()[0][4].starts_with("{{status}}\u{1f}{{position}}\u{1f}{{mpris:trackid}}\u{1f}"));
+        assert_eq!(state.status, TrackStatus::Playing);
+        assert_eq!(state.position, Some(Duration::from_millis(1500)));
+        assert_eq!(state.metadata.mpris_trackid.as_deref(), Some("'/63'"));
+        assert_eq!(state.metadata.mpris_length, Some(4_000_000));
+        assert_eq!(state.metadata.mpris_art_url.as_deref(), Some("file:///a b.png"));
+        assert_eq!(state.metadata.xesam_album.as_deref(), Some("X ;-; Y"));
+        assert_eq!(state.metadata.xesam_album_artist, None);
+        assert_eq!(state.metadata.xesam_artist.as_deref(), Some("Artist"));
+        assert_eq!(state.metadata.xesam_title.as_deref(), Some("A ;-; B"));
+        assert!(!state.metadata.raw.contains_key("xesam:albumArtist"));
+
+        mock([ok(&format!("Stopped{}", "\u{1f}".repeat(10)))]);
+        let state = Playerctl::full_state("mpv").unwrap();
+        assert_eq!(state.status, TrackStatus::Stopped);
+        assert_eq!(state.position, None);
+        assert_eq!(state.metadata, PlayerMetadata::default());
+    }
+
+    #[test]
+    fn seek_range_up_to_length() {
+        mock([ok("1000000;-;4000000\n"), ok("0;-;"), Err(PlayerctlError::NoPlayer)]);
+        assert_eq!(Playerctl::seek_range("mpv").unwrap(), Some((Duration::ZERO, Duration::from_secs(4))));
+        assert_eq!(Playerctl::seek_range("firefox").unwrap(), None);
+        assert!(matches!(Playerctl::seek_range("vlc"), Err(PlayerctlError::NoPlayer)));
+        assert_eq!(calls()[1], ["-p", "firefox", "metadata", "--format", "{{position}};-;{{mpris:length}}"]);
+    }
+
+    #[test]
+    fn remaining_without_length() {
+        mock([ok("1000000;-;")]);
+        assert_eq!(Playerctl::remaining("mpv").unwrap(), None);
     }
 }
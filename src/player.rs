@@ -0,0 +1,201 @@
+//! Controlling a specific player.
+
+use crate::{run_args, LoopStatus, Playerctl, Result};
+
+/// Handle controlling a single named player, created with
+/// [`Playerctl::player`](crate::Playerctl::player).
+///
+/// Every command is sent with `playerctl -p <name>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerInstance {
+    name: String,
+}
+
+impl PlayerInstance {
+    pub(crate) fn new(name: String) -> Self {
+        Self { name }
+    }
+
+    /// The player name commands are sent to.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Command the player to play.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::player("mpv").play().unwrap();
+    /// ```
+    pub fn play(&self) -> Result<()> {
+        self.run(&["play"])?;
+        Ok(())
+    }
+
+    /// Command the player to pause.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::player("mpv").pause().unwrap();
+    /// ```
+    pub fn pause(&self) -> Result<()> {
+        self.run(&["pause"])?;
+        Ok(())
+    }
+
+    /// Command the player to toggle between play/pause.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::player("mpv").play_pause().unwrap();
+    /// ```
+    pub fn play_pause(&self) -> Result<()> {
+        self.run(&["play-pause"])?;
+        Ok(())
+    }
+
+    /// Command the player to stop.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::player("mpv").stop().unwrap();
+    /// ```
+    pub fn stop(&self) -> Result<()> {
+        self.run(&["stop"])?;
+        Ok(())
+    }
+
+    /// Command the player to skip to the next track.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::player("mpv").next().unwrap();
+    /// ```
+    pub fn next(&self) -> Result<()> {
+        self.run(&["next"])?;
+        Ok(())
+    }
+
+    /// Command the player to skip to the previous track.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::player("mpv").previous().unwrap();
+    /// ```
+    pub fn previous(&self) -> Result<()> {
+        self.run(&["previous"])?;
+        Ok(())
+    }
+
+    /// Get the loop status of the player. See [`Playerctl::get_loop_for`]
+    /// for the errors.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// println!("{}", Playerctl::player("mpv").get_loop().unwrap());
+    /// ```
+    pub fn get_loop(&self) -> Result<LoopStatus> {
+        Playerctl::get_loop_for(&self.name)
+    }
+
+    /// Set the loop status of the player. See [`Playerctl::get_loop_for`]
+    /// for the errors.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::{LoopStatus, Playerctl};
+    /// Playerctl::player("mpv").set_loop(LoopStatus::Track).unwrap();
+    /// ```
+    pub fn set_loop(&self, status: LoopStatus) -> Result<()> {
+        Playerctl::set_loop_for(&self.name, status)
+    }
+
+    /// Get whether the player shuffles. See [`Playerctl::get_shuffle_for`]
+    /// for the errors.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// println!("Shuffle: {}", Playerctl::player("mpv").get_shuffle().unwrap());
+    /// ```
+    pub fn get_shuffle(&self) -> Result<bool> {
+        Playerctl::get_shuffle_for(&self.name)
+    }
+
+    /// Turn shuffle on or off for the player. See
+    /// [`Playerctl::get_shuffle_for`] for the errors.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// Playerctl::player("mpv").set_shuffle(true).unwrap();
+    /// ```
+    pub fn set_shuffle(&self, shuffle: bool) -> Result<()> {
+        Playerctl::set_shuffle_for(&self.name, shuffle)
+    }
+
+    /// Toggle shuffle for the player and return whether it shuffles now.
+    /// See [`Playerctl::get_shuffle_for`] for the errors.
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// println!("Shuffle: {}", Playerctl::player("mpv").toggle_shuffle().unwrap());
+    /// ```
+    pub fn toggle_shuffle(&self) -> Result<bool> {
+        Playerctl::toggle_shuffle_for(&self.name)
+    }
+
+    /// Run a playerctl command against this player. The `-p` flag has to
+    /// come before the subcommand.
+    fn run(&self, args: &[&str]) -> Result<String> {
+        let mut argv = vec!["-p", &self.name];
+        argv.extend_from_slice(args);
+        run_args(&argv)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{calls, mock, ok};
+    use crate::{LoopStatus, Playerctl};
+
+    #[test]
+    fn play_pause_targets_player() {
+        mock([ok("")]);
+        Playerctl::player("mpv").play_pause().unwrap();
+        assert_eq!(calls(), [["-p", "mpv", "play-pause"]]);
+    }
+
+    #[test]
+    fn player_name_is_passed_as_single_argument() {
+        mock([ok("")]);
+        let player = Playerctl::player("my player");
+        player.next().unwrap();
+        assert_eq!(player.name(), "my player");
+        assert_eq!(calls(), [["-p", "my player", "next"]]);
+    }
+
+    #[test]
+    fn loop_targets_player() {
+        mock([ok("Playlist"), ok("")]);
+        let player = Playerctl::player("mpv");
+        assert_eq!(player.get_loop().unwrap(), LoopStatus::Playlist);
+        player.set_loop(LoopStatus::None).unwrap();
+        assert_eq!(calls(), [vec!["-p", "mpv", "loop"], vec!["-p", "mpv", "loop", "None"]]);
+    }
+
+    #[test]
+    fn shuffle_targets_player() {
+        mock([ok("Off"), ok(""), ok("On\n"), ok("")]);
+        let player = Playerctl::player("mpv");
+        assert!(!player.get_shuffle().unwrap());
+        player.set_shuffle(true).unwrap();
+        assert!(!player.toggle_shuffle().unwrap());
+        assert_eq!(
+            calls(),
+            [
+                vec!["-p", "mpv", "shuffle"],
+                vec!["-p", "mpv", "shuffle", "On"],
+                vec!["-p", "mpv", "shuffle"],
+                vec!["-p", "mpv", "shuffle", "Off"],
+            ]
+        );
+    }
+}
@@ -0,0 +1,16 @@
+//! Commonly used types, for glob importing.
+//!
+//! ```
+//! use playerctl_rust_wrapper::prelude::*;
+//! ```
+//!
+//! This re-exports [`Playerctl`], the [`PlayerInstance`] and
+//! [`PlayerctlHandle`] handles, [`PlayerctlConfig`], the [`PlayerControls`]
+//! trait, the [`PlayerMetadata`], [`TrackStatus`] and
+//! [`LoopStatus`] types and [`PlayerctlError`]. The crate's `Result` alias is
+//! left out so it doesn't shadow `std::result::Result`.
+
+pub use crate::{
+    LoopStatus, PlayerControls, PlayerInstance, PlayerMetadata, Playerctl, PlayerctlConfig, PlayerctlError,
+    PlayerctlHandle, TrackStatus,
+};
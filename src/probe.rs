@@ -0,0 +1,105 @@
+//! Environment diagnostics.
+
+use std::{fmt, io};
+
+use crate::{Playerctl, PlayerctlError, PlayerctlHandle, Result, Version};
+
+/// Diagnostic report of the playerctl environment, see [`Playerctl::probe`].
+///
+/// Failures are captured in the fields instead of aborting the probe.
+#[derive(Debug)]
+pub struct Probe {
+    /// Whether the `playerctl` binary could be run.
+    pub binary_available: bool,
+    /// The installed playerctl version.
+    pub version: Result<Version>,
+    /// The running players.
+    pub players: Result<Vec<String>>,
+    /// The player commands are sent to by default, `None` if no player is
+    /// running.
+    pub default_player: Result<Option<String>>,
+}
+
+impl Playerctl {
+    /// Gather information about the playerctl environment, e.g. for bug
+    /// reports. This never fails; errors are reported in the [`Probe`].
+    ///
+    /// ```no_run
+    /// # use playerctl_rust_wrapper::Playerctl;
+    /// println!("{}", Playerctl::probe());
+    /// ```
+    pub fn probe() -> Probe {
+        PlayerctlHandle::default().probe()
+    }
+}
+
+impl PlayerctlHandle {
+    /// Gather information about the playerctl environment with the options
+    /// of this handle. See [`Playerctl::probe`].
+    pub fn probe(&self) -> Probe {
+        let version = self.version_parsed();
+        let binary_available = !matches!(
+            &version,
+            Err(PlayerctlError::IoError(e)) if e.kind() == io::ErrorKind::NotFound
+        );
+        let default_player = match self.format("{{playerName}}") {
+            Ok(name) => Ok(Some(name)),
+            Err(PlayerctlError::NoPlayer) => Ok(None),
+            Err(e) => Err(e),
+        };
+        Probe {
+            binary_available,
+            version,
+            players: self.list_players(),
+            default_player,
+        }
+    }
+}
+
+impl fmt::Display for Probe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "playerctl binary: {}", if self.binary_available { "found" } else { "not found" })?;
+        match &self.version {
+            Ok(version) => writeln!(f, "playerctl version: {version}")?,
+            Err(e) => writeln!(f, "playerctl version: error: {e}")?,
+        }
+        match &self.players {
+            Ok(players) if players.is_empty() => writeln!(f, "players: none")?,
+            Ok(players) => writeln!(f, "players: {}", players.join(", "))?,
+            Err(e) => writeln!(f, "players: error: {e}")?,
+        }
+        match &self.default_player {
+            Ok(Some(player)) => write!(f, "default player: {player}"),
+            Ok(None) => write!(f, "default player: none"),
+            Err(e) => write!(f, "default player: error: {e}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{mock, ok};
+    use crate::{Playerctl, PlayerctlError};
+
+    #[test]
+    fn probe_reports_environment() {
+        mock([ok("v2.4.1"), ok("mpv"), ok("mpv\nfirefox")]);
+        let probe = Playerctl::probe();
+        assert!(probe.binary_available);
+        assert_eq!(
+            probe.to_string(),
+            "playerctl binary: found\nplayerctl version: 2.4.1\nplayers: mpv, firefox\ndefault player: mpv"
+        );
+    }
+
+    #[test]
+    fn probe_captures_errors() {
+        let not_found = || Err(PlayerctlError::IoError(std::io::ErrorKind::NotFound.into()));
+        mock([not_found(), not_found(), not_found()]);
+        let probe = Playerctl::probe();
+        assert!(!probe.binary_available);
+        assert!(probe.version.is_err());
+        assert!(probe.players.is_err());
+        assert!(probe.to_string().starts_with("playerctl binary: not found\nplayerctl version: error: IO error"));
+    }
+}
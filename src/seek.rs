@@ -0,0 +1,94 @@
+//! Relative seeking by a signed offset.
+
+use std::{fmt, time::Duration};
+
+use crate::{Playerctl, PlayerctlHandle, Result};
+
+/// An offset to seek by, forward or backward, see [`Playerctl::seek`].
+///
+/// Displayed with its sign, like `+1.5s` or `-10s`.
+///
+/// ```
+/// # use std::time::Duration;
+/// # use playerctl_rust_wrapper::SignedDuration;
+/// assert_eq!(SignedDuration::Backward(Duration::from_secs(10)).to_string(), "-10s");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SignedDuration {
+    /// Seek forward by the duration.
+    Forward(Duration),
+    /// Seek backward by the duration.
+    Backward(Duration),
+}
+
+impl SignedDuration {
+    /// The offset without its direction.
+    pub fn abs(self) -> Duration {
+        match self {
+            SignedDuration::Forward(duration) | SignedDuration::Backward(duration) => duration,
+        }
+    }
+
+    /// Whether the offset seeks backward.
+    pub fn is_backward(self) -> bool {
+        matches!(self, SignedDuration::Backward(_))
+    }
+
+    /// The offset as used by `playerctl position`, e.g. `1.5+` or `10-`.
+    pub(crate) fn as_arg(self) -> String {
+        let sign = if self.is_backward() { '-' } else { '+' };
+        format!("{}{sign}", self.abs().as_secs_f64())
+    }
+}
+
+impl From<Duration> for SignedDuration {
+    /// Take the duration as a forward offset.
+    fn from(duration: Duration) -> Self {
+        SignedDuration::Forward(duration)
+    }
+}
+
+impl fmt::Display for SignedDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.is_backward() { '-' } else { '+' };
+        write!(f, "{sign}{:?}", self.abs())
+    }
+}
+
+impl Playerctl {
+    /// Command the player to seek forward or backward by `offset`.
+    ///
+    /// Unlike [`Playerctl::position`], the direction is explicit rather
+    /// than the sign of a float.
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use playerctl_rust_wrapper::{Playerctl, SignedDuration};
+    /// Playerctl::seek(SignedDuration::Backward(Duration::from_secs(10))).unwrap();
+    /// Playerctl::seek(Duration::from_millis(1500).into()).unwrap();
+    /// ```
+    pub fn seek(offset: SignedDuration) -> Result<()> {
+        PlayerctlHandle::default().seek(offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{calls, mock, ok};
+
+    #[test]
+    fn displays_with_sign() {
+        assert_eq!(SignedDuration::Forward(Duration::from_millis(1500)).to_string(), "+1.5s");
+        assert_eq!(SignedDuration::Backward(Duration::from_millis(250)).to_string(), "-250ms");
+        assert_eq!(format!("{:?}", SignedDuration::Backward(Duration::from_secs(1))), "Backward(1s)");
+    }
+
+    #[test]
+    fn seeks_in_both_directions() {
+        mock([ok(""), ok("")]);
+        Playerctl::seek(Duration::from_millis(1500).into()).unwrap();
+        Playerctl::seek(SignedDuration::Backward(Duration::from_secs(10))).unwrap();
+        assert_eq!(calls(), [["position", "1.5+"], ["position", "10-"]]);
+    }
+}
@@ -0,0 +1,140 @@
+//! Building `--format` templates.
+
+use std::fmt;
+
+/// A piece of a [`FormatTemplate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplatePart {
+    /// A metadata field or expression such as `artist` or `lc(title)`,
+    /// rendered as `{{artist}}`.
+    Field(String),
+    /// Literal text, rendered as-is unless it contains braces.
+    Literal(String),
+}
+
+/// Builder for playerctl `--format` templates.
+///
+/// Literals containing `{{` or `}}` are wrapped in a string expression so
+/// playerctl doesn't parse them as fields.
+///
+/// ```
+/// # use playerctl_rust_wrapper::FormatTemplate;
+/// let template = FormatTemplate::new().field("artist").literal(" - ").field("title");
+/// assert_eq!(template.as_str(), "{{artist}} - {{title}}");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FormatTemplate {
+    template: String,
+}
+
+impl FormatTemplate {
+    /// Create an empty template.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a metadata field or expression.
+    pub fn field(mut self, name: &str) -> Self {
+        self.push(TemplatePart::Field(name.to_owned()));
+        self
+    }
+
+    /// Append literal text.
+    pub fn literal(mut self, text: &str) -> Self {
+        self.push(TemplatePart::Literal(text.to_owned()));
+        self
+    }
+
+    /// The rendered template.
+    pub fn as_str(&self) -> &str {
+        &self.template
+    }
+
+    fn push(&mut self, part: TemplatePart) {
+        match part {
+            TemplatePart::Field(name) => {
+                self.template.push_str("{{");
+                self.template.push_str(&name);
+                self.template.push_str("}}");
+            }
+            TemplatePart::Literal(text) if text.contains("{{") || text.contains("}}") => {
+                self.template.push_str("{{\"");
+                self.template.push_str(&text.replace('\\', "\\\\").replace('"', "\\\""));
+                self.template.push_str("\"}}");
+            }
+            TemplatePart::Literal(text) => self.template.push_str(&text),
+        }
+    }
+}
+
+impl FromIterator<TemplatePart> for FormatTemplate {
+    /// Build a template from its parts.
+    ///
+    /// ```
+    /// # use playerctl_rust_wrapper::{FormatTemplate, TemplatePart};
+    /// let template: FormatTemplate = [
+    ///     TemplatePart::Field("title".to_owned()),
+    ///     TemplatePart::Literal(" by ".to_owned()),
+    ///     TemplatePart::Field("artist".to_owned()),
+    /// ]
+    /// .into_iter()
+    /// .collect();
+    /// assert_eq!(template.as_str(), "{{title}} by {{artist}}");
+    /// ```
+    fn from_iter<I: IntoIterator<Item = TemplatePart>>(iter: I) -> Self {
+        let mut template = Self::new();
+        for part in iter {
+            template.push(part);
+        }
+        template
+    }
+}
+
+impl AsRef<str> for FormatTemplate {
+    fn as_ref(&self) -> &str {
+        &self.template
+    }
+}
+
+impl From<FormatTemplate> for String {
+    fn from(template: FormatTemplate) -> Self {
+        template.template
+    }
+}
+
+impl fmt::Display for FormatTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.template)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_fields_and_literals() {
+        let template = FormatTemplate::new()
+            .field("status")
+            .literal(": ")
+            .field("lc(artist)")
+            .literal(" - ")
+            .field("title");
+        assert_eq!(template.to_string(), "{{status}}: {{lc(artist)}} - {{title}}");
+    }
+
+    #[test]
+    fn escapes_literals_with_braces() {
+        let template = FormatTemplate::new().literal("{{\"x\"}}").field("title");
+        assert_eq!(template.as_str(), r#"{{"{{\"x\"}}"}}{{title}}"#);
+        assert_eq!(FormatTemplate::new().literal("a } b").as_str(), "a } b");
+    }
+
+    #[test]
+    fn collects_parts() {
+        let template: FormatTemplate = [TemplatePart::Literal("[".to_owned()), TemplatePart::Field("position".to_owned())]
+            .into_iter()
+            .collect();
+        assert_eq!(String::from(template), "[{{position}}");
+    }
+}